@@ -0,0 +1,158 @@
+use srs::{Diagnostic, ErrorKind, ParseError, RustifyError, Span};
+use std::fmt::{Display, Write};
+
+/// Input file, used to show the source of the diagnostics.
+pub struct Source<'a> {
+    /// Name of the file (e.g. `<stdin>`).
+    pub name: &'a str,
+    /// Content of the file.
+    pub text: &'a str,
+    /// Use ANSI colors?
+    pub color: bool,
+}
+
+impl Source<'_> {
+    /// Renders a rustc-style error `message`, showing the source underlined at `span`, if any,
+    /// followed by a `help` note, if any.
+    pub fn render(
+        &self,
+        message: impl Display,
+        span: Option<Span>,
+        help: Option<String>,
+    ) -> String {
+        let [red, blue, bold, reset] = if self.color {
+            ["\x1b[1;31m", "\x1b[1;34m", "\x1b[1m", "\x1b[0m"]
+        } else {
+            [""; 4]
+        };
+
+        let mut res = format!("{red}error{reset}{bold}: {message}{reset}\n");
+        let mut gutter = String::new();
+        if let Some(Span { start, end }) = span {
+            let line = self.text.lines().nth(start.line - 1).unwrap_or_default();
+            let lineno = start.line.to_string();
+            gutter = " ".repeat(lineno.len());
+            // Spans ending on another line are underlined up to the end of their first line
+            let width = if end.line == start.line {
+                end.column.saturating_sub(start.column)
+            } else {
+                (line.chars().count() + 1).saturating_sub(start.column)
+            };
+
+            writeln!(
+                res,
+                "{gutter}{blue}-->{reset} {}:{}:{}",
+                self.name, start.line, start.column
+            )
+            .unwrap();
+            writeln!(res, "{gutter} {blue}|{reset}").unwrap();
+            writeln!(res, "{blue}{lineno} |{reset} {line}").unwrap();
+            writeln!(
+                res,
+                "{gutter} {blue}|{reset} {}{red}{}{reset}",
+                " ".repeat(start.column - 1),
+                "^".repeat(width.max(1))
+            )
+            .unwrap();
+        }
+        if let Some(help) = help {
+            writeln!(res, "{gutter} {blue}={reset} {bold}help{reset}: {help}").unwrap();
+        }
+        res
+    }
+
+    /// Renders the error `diagnostic`, with a suggestion to fix it, if any.
+    pub fn render_diagnostic(&self, diagnostic: &Diagnostic) -> String {
+        let help = match &diagnostic.kind {
+            ErrorKind::Parse(kind) => parse_help(kind),
+            ErrorKind::Rustify(kind) => rustify_help(kind),
+            _ => None,
+        };
+        self.render(&diagnostic.kind, diagnostic.span, help)
+    }
+}
+
+/// Returns a suggestion to fix a parse error, if any.
+fn parse_help(kind: &ParseError) -> Option<String> {
+    Some(match kind {
+        ParseError::Unexpected('\\') => {
+            "backslashes are allowed only in strings and character literals".into()
+        }
+        ParseError::Unexpected(_) => return None,
+        ParseError::AtomOutsideList(_) => "wrap it in a list (e.g. `(f x)`)".into(),
+        ParseError::Missing(c) => format!("add the missing `{}`", c),
+        ParseError::TooMuch(c) => format!("remove this `{}`", c),
+        ParseError::WrongClose(open, _) => format!(
+            "`{}` must be closed by `{}`",
+            open,
+            match open {
+                '(' => ')',
+                '[' => ']',
+                _ => '>',
+            }
+        ),
+        ParseError::UnterminatedComment => "close the block comment with `|#`".into(),
+        ParseError::UnterminatedChar => {
+            "close the character literal with `'`, lifetimes start with a letter or `_`".into()
+        }
+        ParseError::InvalidChar(_) => {
+            "character literals contain a single (ASCII for bytes) character or escape".into()
+        }
+    })
+}
+
+/// Returns a suggestion to fix a rustify error, if any.
+fn rustify_help(kind: &RustifyError) -> Option<String> {
+    match kind {
+        RustifyError::UnexpectedFunctionName(_) => {
+            Some("a list must start with a function, an operator or a keyword".into())
+        }
+        RustifyError::ExpectedMatchCondition => {
+            Some("match arms are written as `(pattern body...)`".into())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use srs::Position;
+
+    #[test]
+    fn render() {
+        let source = Source {
+            name: "main.srs",
+            text: "(fn main ()\n  (match))",
+            color: false,
+        };
+        let start = Position {
+            offset: 15,
+            line: 2,
+            column: 4,
+        };
+        let end = Position {
+            offset: 20,
+            line: 2,
+            column: 9,
+        };
+        assert_eq!(
+            source.render(
+                "Missing arguments for 'match'",
+                Some(Span { start, end }),
+                Some("add a value".into())
+            ),
+            "error: Missing arguments for 'match'
+ --> main.srs:2:4
+  |
+2 |   (match))
+  |    ^^^^^
+  = help: add a value
+"
+        );
+        assert_eq!(
+            source.render("Syntax error", None, None),
+            "error: Syntax error\n"
+        );
+    }
+}
@@ -1,9 +1,13 @@
 use clap::Parser;
 use std::{
-    fs::File,
-    io::{self, BufRead, BufReader, BufWriter, Write},
+    fs::{self, File},
+    io::{self, BufWriter, IsTerminal, Read, Write},
+    process,
 };
 
+mod diagnostic;
+use diagnostic::Source;
+
 /// S-expression to Rust transpiler
 #[derive(Parser, Debug)]
 #[clap(author, version, about)]
@@ -15,45 +19,79 @@ struct Args {
     /// rs output file. - write to stdout
     #[clap(default_value = "-")]
     output: String,
+
+    /// Keep comments: doc comments (;;; and ;;;!) as documentation, top-level plain ones as line
+    /// comments (plain comments inside a form are dropped)
+    #[clap(short, long)]
+    comments: bool,
 }
 
-/// Panics with a custom message, without other informations.
-macro_rules! clean_panic {
-    ($($arg : tt) *) => {{
-        std::panic::set_hook(Box::new(|info| {
-            if let Some(s) = info.payload().downcast_ref::<String>() {
-                eprintln!("{}", s);
-            }
-        }));
-        panic!($($arg)*);
-    }};
+/// Prints the diagnostic `diagnostic` and exits with an error.
+fn fail(diagnostic: String) -> ! {
+    eprint!("{}", diagnostic);
+    process::exit(1)
 }
 
 fn main() {
     let args = Args::parse();
 
-    let input: Box<dyn BufRead> = match args.input.as_str() {
-        "-" => Box::new(BufReader::new(io::stdin())),
-        path => Box::new(BufReader::new(File::open(path).unwrap())),
+    // Diagnostics go to stderr, so colors are used only if it is a terminal
+    let color = io::stderr().is_terminal();
+    let no_source = Source {
+        name: "",
+        text: "",
+        color,
+    };
+
+    let (name, text) = match args.input.as_str() {
+        "-" => {
+            let mut text = String::new();
+            io::stdin()
+                .read_to_string(&mut text)
+                .map(|_| ("<stdin>", text))
+        }
+        path => fs::read_to_string(path).map(|text| (path, text)),
+    }
+    .unwrap_or_else(|e| {
+        fail(no_source.render(
+            format_args!("Cannot read `{}`. {}", args.input, e),
+            None,
+            None,
+        ))
+    });
+    let source = Source {
+        name,
+        text: &text,
+        color,
     };
 
     let mut output: Box<dyn Write> = match args.output.as_str() {
         "-" => Box::new(BufWriter::new(io::stdout())),
-        path => Box::new(BufWriter::new(File::create(path).unwrap())),
+        path => Box::new(BufWriter::new(File::create(path).unwrap_or_else(|e| {
+            fail(source.render(format_args!("Cannot create `{}`. {}", path, e), None, None))
+        }))),
+    };
+
+    // Every erroneous form is reported, going on with the next ones
+    let parsed_exps: Box<dyn Iterator<Item = _>> = if args.comments {
+        Box::new(srs::parse_with_comments(&text))
+    } else {
+        Box::new(srs::parse(&text))
     };
+    let res = srs::transpile_parsed(parsed_exps).unwrap_or_else(|diagnostics| {
+        for diagnostic in &diagnostics {
+            eprintln!("{}", source.render_diagnostic(diagnostic));
+        }
+        fail(source.render(
+            format_args!(
+                "Aborting due to {} previous error{}",
+                diagnostics.len(),
+                if diagnostics.len() == 1 { "" } else { "s" }
+            ),
+            None,
+            None,
+        ))
+    });
 
-    let parsed_exps = srs::parse_lines(input.lines().map(Result::unwrap));
-    let token_stream = FromIterator::from_iter(parsed_exps.map(|x| match x {
-        Ok(res) => srs::rustify(&res).unwrap_or_else(|e| clean_panic!("Error. {}", e)),
-        Err(e) => clean_panic!("Parse error. {}", e),
-    }));
-
-    write!(
-        output,
-        "{}",
-        prettyplease::unparse(
-            &syn::parse2(token_stream).unwrap_or_else(|e| clean_panic!("Syntax error: {}", e)) // TODO better feedback
-        )
-    )
-    .unwrap()
+    write!(output, "{}", res).unwrap()
 }
@@ -5,22 +5,70 @@
 
 #[macro_use]
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
-use core::fmt::{self, Display};
+use alloc::string::String;
+use core::fmt::{self, Debug, Display};
 
 pub mod parser;
-pub use parser::{parse, parse_lines};
+pub use parser::{parse, parse_lines, parse_lines_with_comments, parse_with_comments, ParseError};
 
 pub mod rustify;
-pub use rustify::rustify;
+pub use rustify::{rustify, RustifyError};
+
+#[cfg(feature = "validate")]
+mod validate;
+#[cfg(feature = "validate")]
+pub use validate::rustify_validated;
+
+#[cfg(feature = "pretty")]
+mod transpile;
+#[cfg(feature = "pretty")]
+pub use transpile::{transpile, transpile_file, transpile_parsed};
+
+/// A position in the input.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct Position {
+    /// Byte offset from the beginning of the input.
+    ///
+    /// The lines given to [`parse_lines`] are assumed to end with `\n`, or with `\r\n` if they
+    /// still end with `\r`: offsets drift on the lines stripped of `\r\n` (e.g. by
+    /// `BufRead::lines`), unlike the line and the column.
+    pub offset: usize,
+    /// Line number (1-based).
+    pub line: usize,
+    /// Column number, in characters (1-based).
+    pub column: usize,
+}
+
+impl Position {
+    /// Returns the position following the character `c` at this position.
+    pub(crate) fn advance(self, c: char) -> Self {
+        Self {
+            offset: self.offset + c.len_utf8(),
+            column: self.column + 1,
+            ..self
+        }
+    }
+}
+
+/// A range of the input, from `start` (included) to `end` (excluded).
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct Span {
+    /// Position of the first character.
+    pub start: Position,
+    /// Position following the last character.
+    pub end: Position,
+}
 
 /// Represents an error occurred during parsing or rustifying.
 ///
 /// You can print those errors with `eprintln!("{}", error);`
 #[derive(Debug, Eq, PartialEq)]
 pub struct Error<Kind: Display> {
-    /// Line number of the input where the error occurs.
-    pub lineno: Option<usize>,
+    /// Part of the input where the error occurs.
+    pub span: Option<Span>,
     /// Type of error occurred.
     pub kind: Kind,
 }
@@ -28,9 +76,79 @@ pub struct Error<Kind: Display> {
 impl<Kind: Display> Display for Error<Kind> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.kind)?;
-        if let Some(lineno) = self.lineno {
-            write!(f, " on line {}", lineno)?
+        if let Some(Span { start, .. }) = self.span {
+            write!(f, " on line {}, column {}", start.line, start.column)?
         }
         write!(f, ".")
     }
 }
+
+#[cfg(feature = "std")]
+impl<Kind: Display + Debug> std::error::Error for Error<Kind> {}
+
+/// Any error occurred transpiling, from parsing to the generated code.
+#[derive(Debug)]
+pub enum ErrorKind {
+    /// Error occurred during parsing.
+    Parse(ParseError),
+    /// Error occurred during rustifying.
+    Rustify(RustifyError),
+    /// The generated code is not valid Rust.
+    Syntax(
+        /// Description of the syntax error.
+        String,
+    ),
+    /// The input cannot be read.
+    Io(
+        /// Description of the I/O error.
+        String,
+    ),
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(e) => write!(f, "{}", e),
+            Self::Rustify(e) => write!(f, "{}", e),
+            Self::Syntax(e) => write!(f, "Syntax error in the generated code. {}", e),
+            Self::Io(e) => write!(f, "Cannot read the input. {}", e),
+        }
+    }
+}
+
+/// Represents any error occurred transpiling, so that parse and rustify errors can be propagated
+/// together with `?`.
+///
+/// # Example
+///
+/// ```
+/// fn transpile(input: &str) -> Result<String, srs::Diagnostic> {
+///     let mut res = String::new();
+///     for exp in srs::parse(input) {
+///         res += &srs::rustify(&exp?)?.to_string();
+///     }
+///     Ok(res)
+/// }
+///
+/// assert_eq!(transpile("(f x)").unwrap(), "f (x) ;");
+/// assert!(transpile("(f x").is_err());
+/// ```
+pub type Diagnostic = Error<ErrorKind>;
+
+impl From<Error<ParseError>> for Diagnostic {
+    fn from(e: Error<ParseError>) -> Self {
+        Self {
+            span: e.span,
+            kind: ErrorKind::Parse(e.kind),
+        }
+    }
+}
+
+impl From<Error<RustifyError>> for Diagnostic {
+    fn from(e: Error<RustifyError>) -> Self {
+        Self {
+            span: e.span,
+            kind: ErrorKind::Rustify(e.kind),
+        }
+    }
+}
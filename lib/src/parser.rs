@@ -1,11 +1,13 @@
 use alloc::{string::String, vec::Vec};
 use core::{
     fmt::{self, Display},
+    iter::Peekable,
     mem,
+    str::Chars,
 };
 use itertools::Itertools;
 
-use crate::Error;
+use crate::{rustify::doc_comment, Error, Position, Span};
 
 /// Represents an S-expression.
 #[derive(Debug)]
@@ -14,15 +16,37 @@ pub enum Sexp {
     Atom {
         /// The raw value of the atom.
         val: String,
-        /// Its line number in the input.
-        lineno: usize,
+        /// Its position in the input.
+        span: Span,
     },
     /// This expression is a list.
-    List(Vec<Sexp>),
+    List(Vec<Sexp>, Span),
     /// This expression is an array.
-    Array(Vec<Sexp>),
+    Array(Vec<Sexp>, Span),
     /// This expression is a list of generics.
-    Generics(Vec<Sexp>),
+    Generics(Vec<Sexp>, Span),
+    /// This is a line comment: a doc comment (e.g. `;;; outer`, `;;;! inner`), or any other
+    /// comment (e.g. `;; note`) kept only by [`parse_with_comments`] and
+    /// [`parse_lines_with_comments`].
+    Comment {
+        /// The raw text of the comment, including the leading `;`.
+        val: String,
+        /// Its position in the input.
+        span: Span,
+    },
+}
+
+impl Sexp {
+    /// Returns the position of this expression in the input, including the group delimiters.
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Atom { span, .. }
+            | Self::List(_, span)
+            | Self::Array(_, span)
+            | Self::Generics(_, span)
+            | Self::Comment { span, .. } => *span,
+        }
+    }
 }
 
 /// An error occurred during parsing.
@@ -43,6 +67,16 @@ pub enum ParseError {
         /// Closing character.
         char,
     ),
+    /// A block comment is not closed by `|#`.
+    UnterminatedComment,
+    /// A character literal is not closed by `'`.
+    UnterminatedChar,
+    /// A character literal is empty, contains an invalid escape or, for byte characters, a
+    /// non-ASCII character.
+    InvalidChar(
+        /// The literal read so far.
+        String,
+    ),
 }
 
 impl Display for ParseError {
@@ -53,12 +87,22 @@ impl Display for ParseError {
             Self::Missing(c) => write!(f, "Missing '{}'", c),
             Self::TooMuch(c) => write!(f, "Too much '{}'", c),
             Self::WrongClose(open, close) => write!(f, "'{}' closed by '{}'", open, close),
+            Self::UnterminatedComment => write!(f, "Unterminated block comment"),
+            Self::UnterminatedChar => write!(f, "Unterminated character literal"),
+            Self::InvalidChar(c) => write!(f, "Invalid character literal `{}`", c),
         }
     }
 }
 
 /// Parses a string into an iterator of possible s-expressions.
 ///
+/// Doc comments (`;;; outer`, `;;;! inner`) are kept as [`Sexp::Comment`], while the other
+/// comments are discarded.
+///
+/// After an error, the rest of the erroneous form is skipped: parsing resumes at the next line
+/// not starting with a whitespace or a closing delimiter, which is assumed to begin a new
+/// top-level form.
+///
 /// If you have an iterator (e.g. stdin or a file) it is preferred to use [`parse_lines`] since the
 /// latter doesn't consume the input iterator.
 #[inline]
@@ -68,9 +112,14 @@ pub fn parse(input: &str) -> impl Iterator<Item = Result<Sexp, Error<ParseError>
 
 /// Parses an stringifiable (text) iterator into an iterator of possible s-expressions.
 ///
+/// As for [`parse`], only doc comments are kept.
+///
 /// The input iterator is not consumed by this function, so it is preferable to use this, rather
 /// than [`parse`], if you have already an iterator for your input (e.g. stdin or a file).
 ///
+/// The lines are assumed to be terminated by `\n` (`\r\n` if they end with `\r`), when computing
+/// the byte [offsets](crate::Position::offset) of the spans.
+///
 /// # Example
 ///
 /// ```
@@ -79,10 +128,10 @@ pub fn parse(input: &str) -> impl Iterator<Item = Result<Sexp, Error<ParseError>
 /// # use std::io::BufRead;
 /// # let stdin = std::io::Cursor::new("(f x)");
 /// # macro_rules! println {
-/// #   ("{}", $v: tt) => { assert_eq!("f (x) ;", $v.to_string()); }
+/// #   ("{}", $v: tt) => { assert_eq!("f (x) ;", $v.to_string()) }
 /// # }
 /// # macro_rules! eprintln {
-/// #   ($($e: tt) *) => { panic!($($e)*); }
+/// #   ($($e: tt) *) => { panic!($($e)*) }
 /// # }
 ///
 /// for x in parse_lines(stdin.lines().map(Result::unwrap)) {
@@ -95,16 +144,73 @@ pub fn parse(input: &str) -> impl Iterator<Item = Result<Sexp, Error<ParseError>
 ///     }
 /// }
 /// ```
+#[inline]
 pub fn parse_lines(
     input: impl Iterator<Item = impl Into<String>>,
+) -> impl Iterator<Item = Result<Sexp, Error<ParseError>>> {
+    lines_to_sexps(input, false)
+}
+
+/// Parses a string like [`parse`], keeping all the line comments as [`Sexp::Comment`].
+///
+/// Only the top-level plain comments (e.g. `;; note`) can be written by `transpile_parsed`, the
+/// ones inside a form (e.g. in a function body) produce no code.
+#[inline]
+pub fn parse_with_comments(
+    input: &str,
+) -> impl Iterator<Item = Result<Sexp, Error<ParseError>>> + '_ {
+    parse_lines_with_comments(input.split('\n'))
+}
+
+/// Parses an stringifiable (text) iterator like [`parse_lines`], keeping all the line comments as
+/// [`Sexp::Comment`].
+///
+/// Doc comments (`;;; outer`, `;;;! inner`) are rustified into documentation attributes, while
+/// other comments produce no Rust code, since they cannot be represented in a token stream.
+/// `transpile_parsed` still writes the top-level ones as line comments.
+#[inline]
+pub fn parse_lines_with_comments(
+    input: impl Iterator<Item = impl Into<String>>,
+) -> impl Iterator<Item = Result<Sexp, Error<ParseError>>> {
+    lines_to_sexps(input, true)
+}
+
+/// Parses an stringifiable (text) iterator, keeping all the line comments if `comments`, only the
+/// doc comments otherwise.
+fn lines_to_sexps(
+    input: impl Iterator<Item = impl Into<String>>,
+    comments: bool,
 ) -> impl Iterator<Item = Result<Sexp, Error<ParseError>>> {
     let mut string_mode = false;
     let mut escape_mode = false;
+    // Number of `#` closing the current raw string, if any
+    let mut raw_string_hashes = None;
+
+    // Depth of nested block comments (e.g. `#| a #| b |# |#`)
+    let mut block_comments = 0;
+    // Scope depths of the pending datum comments (e.g. `#; (f x)`)
+    let mut datum_comments = Vec::<usize>::new();
+
+    // Position of the outermost block comment opening
+    let mut block_comment_start = Position::default();
+
+    // Current token, which can span multiple lines if it is a string literal, and its start
+    let mut token = String::new();
+    let mut token_start = Position::default();
+
+    // Offset of the next line
+    let mut offset = 0;
+    // Position of the end of the last line
+    let mut end = Position::default();
+    // Skipping the rest of a form after an error?
+    let mut recovering = false;
+    // Error to return after the forms completed before it
+    let mut pending_error = None;
 
     // Contains expressions not already pushed into resulting iterator
     let mut expressions = Vec::<Sexp>::new();
-    // Contains expressions scope stack
-    let mut scopes = Vec::<(Vec<Sexp>, char)>::new();
+    // Contains expressions scope stack, with the opening position
+    let mut scopes = Vec::<(Vec<Sexp>, char, Position)>::new();
 
     /// Returns the matching grouping character.
     ///
@@ -126,45 +232,148 @@ pub fn parse_lines(
     input
         .enumerate()
         .batching(move |iterator| loop {
+            if let Some(e) = pending_error.take() {
+                return Some(Err(e));
+            }
+
+            /// Returns the error `e`, after the forms completed before it on the same line (e.g.
+            /// `(f) (g \`), then skips the rest of the erroneous form.
+            macro_rules! fail {
+                ($e: expr) => {{
+                    recovering = true;
+                    let e = $e;
+                    if expressions.is_empty() {
+                        return Some(Err(e));
+                    }
+                    pending_error = Some(e);
+                    return Some(Ok(mem::take(&mut expressions)));
+                }};
+            }
+
             match iterator.next() {
                 Some((lineno, line)) => {
-                    let mut token = String::new();
+                    let mut line: String = line.into();
+                    let line_offset = offset;
+                    // The line terminator is not part of `line`, assumed to be `\n`
+                    offset += line.len() + 1;
+                    // The `\r` of a `\r\n` terminator left by splitting on `\n`
+                    if line.ends_with('\r') {
+                        line.pop();
+                    }
 
-                    // Lines are 1-based
-                    let lineno = lineno + 1;
+                    let mut chars = Cursor::new(
+                        &line,
+                        Position {
+                            offset: line_offset,
+                            // Lines and columns are 1-based
+                            line: lineno + 1,
+                            column: 1,
+                        },
+                    );
+
+                    if recovering {
+                        // Resynchronises at the next top-level form, which starts at the
+                        // beginning of a line, discarding the rest of the erroneous one (e.g. its
+                        // closing parenthesis on a line of its own)
+                        if !line.starts_with(|c: char| {
+                            !c.is_whitespace() && !matches!(c, ')' | ']' | '>')
+                        }) {
+                            continue;
+                        }
+                        recovering = false;
+                        (string_mode, escape_mode, raw_string_hashes) = (false, false, None);
+                        block_comments = 0;
+                        datum_comments.clear();
+                        token.clear();
+                        scopes.clear();
+                    }
+
+                    if string_mode || raw_string_hashes.is_some() {
+                        // The string literal continues on this line
+                        token.push('\n');
+                        escape_mode = false;
+                    }
+
+                    // Position of the current character
+                    let mut position;
 
                     /// Closes the current token, if any.
                     macro_rules! close_token {
-                        (reassign) => { token = String::new() };
-                        (reassign not_allocate) => {};
-                        ($($x: ident)?) => {
+                        () => {{
                             if !token.is_empty() {
-                                if let Some(scope) = scopes.last_mut() {
-                                    scope.0.push(Sexp::Atom { val: token, lineno });
+                                let val = mem::take(&mut token);
+                                let span = Span { start: token_start, end: position };
+                                if datum_comments.last() == Some(&scopes.len()) {
+                                    datum_comments.pop();
+                                } else if let Some(scope) = scopes.last_mut() {
+                                    scope.0.push(Sexp::Atom { val, span });
                                 } else {
-                                    return Some(Err(Error{
-                                        lineno: Some(lineno),
-                                        kind: ParseError::AtomOutsideList(token)
-                                    }))
+                                    fail!(Error {
+                                        span: Some(span),
+                                        kind: ParseError::AtomOutsideList(val)
+                                    })
                                 }
-                                close_token!(reassign $($x)?);
                             }
-                        };
+                            token_start = position;
+                        }};
                     }
 
-                    let lineno = Some(lineno);
+                    /// Returns the error `kind`, occurred from the current character to the
+                    /// cursor.
+                    macro_rules! error {
+                        ($kind: expr) => {
+                            fail!(Error {
+                                span: Some(Span { start: position, end: chars.position() }),
+                                kind: $kind,
+                            })
+                        };
+                    }
 
-                    for c in line.into().chars() {
+                    while let Some(c) = {
+                        position = chars.position();
+                        chars.next()
+                    } {
+                        if token.is_empty() {
+                            token_start = position;
+                        }
                         match c {
+                            // Block comments content, which can be nested
+                            _ if block_comments > 0 => match (c, chars.peek()) {
+                                ('|', Some('#')) => {
+                                    chars.next();
+                                    block_comments -= 1
+                                }
+                                ('#', Some('|')) => {
+                                    chars.next();
+                                    block_comments += 1
+                                }
+                                _ => (),
+                            },
+                            // Raw strings (e.g. `r#"a "b""#`, `br"\d"`, `cr"a"`)
+                            '\"' if raw_string_hashes.is_none()
+                                && !string_mode
+                                && is_raw_prefix(&token) =>
+                            {
+                                token.push(c);
+                                raw_string_hashes = Some(token.matches('#').count())
+                            }
+                            '\"' if raw_string_hashes.is_some_and(|n| {
+                                let mut ahead = chars.clone();
+                                (0..n).all(|_| ahead.next() == Some('#'))
+                            }) =>
+                            {
+                                token.push(c);
+                                for _ in 0..raw_string_hashes.take().unwrap_or_default() {
+                                    token.extend(chars.next())
+                                }
+                            }
+                            _ if raw_string_hashes.is_some() => token.push(c),
                             '\\' => {
                                 if string_mode {
                                     token += "\\";
                                     escape_mode = true
                                 } else {
-                                    return Some(Err(Error {
-                                        lineno,
-                                        kind: ParseError::Unexpected('\\'),
-                                    }));
+                                    error!(ParseError::Unexpected('\\'));
                                 }
                             }
                             '\"' if !escape_mode => {
@@ -175,39 +384,97 @@ pub fn parse_lines(
                                 token += &String::from(c);
                                 escape_mode = false
                             }
+                            // Block comments (e.g. `#| a |#`)
+                            '#' if chars.next_if_eq(&'|').is_some() => {
+                                close_token!();
+                                block_comment_start = position;
+                                block_comments += 1
+                            }
+                            // Datum comments, commenting out the following expression (e.g.
+                            // `#; (f x)`)
+                            '#' if chars.next_if_eq(&';').is_some() => {
+                                close_token!();
+                                datum_comments.push(scopes.len())
+                            }
+                            // Comparison and shift operators (e.g. `<`, `<=`, `<<=`)
+                            '<' if matches!(chars.peek(), None | Some(' ' | '=' | '<')) => {
+                                token += &String::from(c)
+                            }
+                            // Operators ending with `>` (e.g. `->`, `=>`, `>=`, `>>`) outside
+                            // generics
+                            '>' if token.ends_with(&['-', '='][..])
+                                || matches!(scopes.last(), Some((_, opened_by, _)) if *opened_by != '<') =>
+                            {
+                                token += &String::from(c)
+                            }
+                            // Character literals (e.g. `'a'`, `b'a'`) and lifetimes (e.g. `'a`,
+                            // `&'a`)
+                            '\'' if token.is_empty() || token == "b" || token == "&" => {
+                                let mut ahead = chars.clone();
+                                match (ahead.next(), ahead.next()) {
+                                    // A character literal stays glued to `&` (e.g. `&'a'`)
+                                    (Some('\\' | '\''), _) | (Some(_), Some('\'')) => {
+                                        match char_to_string(&mut chars, token == "b") {
+                                            Ok(literal) => token += &literal,
+                                            Err(kind) => error!(kind),
+                                        }
+                                    }
+                                    (Some(l), _) if token != "b"
+                                        && (l.is_alphabetic() || l == '_') =>
+                                    {
+                                        // Lifetime of a reference (e.g. `&'a`)
+                                        if token == "&" {
+                                            close_token!();
+                                        }
+                                        token.push(c)
+                                    }
+                                    _ => error!(ParseError::UnterminatedChar),
+                                }
+                            }
                             par @ ('(' | '[' | '<') => {
                                 close_token!();
-                                scopes.push((Vec::new(), par))
+                                scopes.push((Vec::new(), par, position))
                             }
                             par @ (')' | ']' | '>') => {
                                 close_token!();
-                                if let Some((closed, opened_by)) = scopes.pop() {
+                                if let Some((closed, opened_by, start)) = scopes.pop() {
                                     if par != pair_of(opened_by) {
-                                        return Some(Err(Error {
-                                            lineno,
-                                            kind: ParseError::WrongClose(opened_by, par),
-                                        }));
+                                        error!(ParseError::WrongClose(opened_by, par));
                                     }
+                                    let span = Span { start, end: chars.position() };
                                     let closed = match par {
-                                        ')' => Sexp::List(closed),
-                                        ']' => Sexp::Array(closed),
-                                        '>' => Sexp::Generics(closed),
+                                        ')' => Sexp::List(closed, span),
+                                        ']' => Sexp::Array(closed, span),
+                                        '>' => Sexp::Generics(closed, span),
                                         _ => unreachable!(),
                                     };
-                                    if let Some(scope) = scopes.last_mut() {
+                                    // Datum comments without datum in the closed group
+                                    datum_comments.retain(|depth| *depth <= scopes.len());
+                                    if datum_comments.last() == Some(&scopes.len()) {
+                                        datum_comments.pop();
+                                    } else if let Some(scope) = scopes.last_mut() {
                                         scope.0.push(closed);
                                     } else {
                                         expressions.push(closed)
                                     }
                                 } else {
-                                    return Some(Err(Error {
-                                        lineno,
-                                        kind: ParseError::TooMuch(par),
-                                    }));
+                                    error!(ParseError::TooMuch(par));
                                 }
                             }
                             ';' => {
                                 close_token!();
+                                let val: String = core::iter::once(c).chain(chars.by_ref()).collect();
+                                if comments || doc_comment(&val).is_some() {
+                                    let comment = Sexp::Comment {
+                                        val,
+                                        span: Span { start: position, end: chars.position() },
+                                    };
+                                    if let Some(scope) = scopes.last_mut() {
+                                        scope.0.push(comment);
+                                    } else {
+                                        expressions.push(comment)
+                                    }
+                                }
                                 break;
                             }
                             ' ' => close_token!(),
@@ -215,19 +482,52 @@ pub fn parse_lines(
                         }
                     }
 
-                    close_token!(not_allocate);
+                    position = chars.position();
+                    end = position;
+                    if !string_mode && raw_string_hashes.is_none() {
+                        close_token!();
+                    }
 
                     if scopes.is_empty() {
                         return Some(Ok(mem::take(&mut expressions)));
                     }
                 }
+                // The erroneous form is already reported, whatever is left open
+                None if recovering => return None,
+                None if block_comments > 0 => {
+                    let e = Error {
+                        span: Some(Span {
+                            start: block_comment_start,
+                            end: block_comment_start.advance('#').advance('|'),
+                        }),
+                        kind: ParseError::UnterminatedComment,
+                    };
+                    // Avoids infinite re-entering in this case
+                    block_comments = 0;
+                    scopes.clear();
+                    fail!(e);
+                }
+                None if string_mode || raw_string_hashes.is_some() => {
+                    let e = Error {
+                        span: Some(Span { start: token_start, end }),
+                        kind: ParseError::Missing('"'),
+                    };
+                    // Avoids infinite re-entering in this case
+                    (string_mode, raw_string_hashes) = (false, None);
+                    scopes.clear();
+                    fail!(e);
+                }
                 None if !scopes.is_empty() => {
+                    let (_, opened_by, start) = scopes.last().unwrap();
                     let e = Error {
-                        lineno: None,
-                        kind: ParseError::Missing(pair_of(scopes.last().unwrap().1)),
+                        span: Some(Span {
+                            start: *start,
+                            end: start.advance(*opened_by),
+                        }),
+                        kind: ParseError::Missing(pair_of(*opened_by)),
                     };
                     scopes.clear(); // Avoids infinite re-entering in this case
-                    return Some(Err(e));
+                    fail!(e);
                 }
                 _ => return None,
             }
@@ -239,12 +539,152 @@ pub fn parse_lines(
         })
 }
 
+/// An iterator over the characters of a line, keeping track of their position in the input.
+#[derive(Clone)]
+struct Cursor<'a> {
+    chars: Peekable<Chars<'a>>,
+    position: Position,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(line: &'a str, start: Position) -> Self {
+        Self {
+            chars: line.chars().peekable(),
+            position: start,
+        }
+    }
+
+    /// Returns the position of the next character.
+    fn position(&self) -> Position {
+        self.position
+    }
+
+    fn peek(&mut self) -> Option<&char> {
+        self.chars.peek()
+    }
+
+    fn next_if(&mut self, func: impl FnOnce(&char) -> bool) -> Option<char> {
+        match self.peek() {
+            Some(c) if func(c) => self.next(),
+            _ => None,
+        }
+    }
+
+    fn next_if_eq(&mut self, expected: &char) -> Option<char> {
+        self.next_if(|c| c == expected)
+    }
+}
+
+impl Iterator for Cursor<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        self.position = self.position.advance(c);
+        Some(c)
+    }
+}
+
+/// Checks if `token` is the prefix of a raw string (e.g. `r#`, `br`, `cr##`).
+fn is_raw_prefix(token: &str) -> bool {
+    ["r", "br", "cr"].iter().any(|p| {
+        token
+            .strip_prefix(p)
+            .is_some_and(|h| h.chars().all(|c| c == '#'))
+    })
+}
+
+/// Reads a character literal (e.g. `'a'`, `'\n'`, `'\u{1F600}'`), whose opening `'` was already
+/// read.
+///
+/// If `byte`, the character must be ASCII (e.g. `b'a'`, `b'\x7F'`).
+fn char_to_string(chars: &mut Cursor, byte: bool) -> Result<String, ParseError> {
+    let mut res = String::from('\'');
+    let invalid = |mut res: String, c: Option<char>| {
+        res.extend(c);
+        ParseError::InvalidChar(res)
+    };
+    match chars.next() {
+        Some('\\') => {
+            res.push('\\');
+            match chars.next() {
+                Some(e @ ('n' | 'r' | 't' | '\\' | '0' | '\'' | '"')) => res.push(e),
+                Some('x') => {
+                    res.push('x');
+                    let mut code = String::new();
+                    for _ in 0..2 {
+                        match chars.next() {
+                            Some(d) if d.is_ascii_hexdigit() => code.push(d),
+                            d => return Err(invalid(res + &code, d)),
+                        }
+                    }
+                    res += &code;
+                    // Only ASCII characters can be written with `\x`, except for bytes
+                    if !byte && u8::from_str_radix(&code, 16).is_ok_and(|x| !x.is_ascii()) {
+                        return Err(invalid(res, None));
+                    }
+                }
+                Some('u') if !byte => {
+                    res.push('u');
+                    if chars.next_if_eq(&'{').is_none() {
+                        return Err(invalid(res, chars.next()));
+                    }
+                    res.push('{');
+                    let mut code = String::new();
+                    while let Some(d) = chars.next_if(char::is_ascii_hexdigit) {
+                        code.push(d);
+                    }
+                    res += &code;
+                    match chars.next() {
+                        Some('}') => res.push('}'),
+                        d => return Err(invalid(res, d)),
+                    }
+                    if u32::from_str_radix(&code, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .is_none()
+                    {
+                        return Err(invalid(res, None));
+                    }
+                }
+                e => return Err(invalid(res, e)),
+            }
+        }
+        Some('\'') => return Err(invalid(res, Some('\''))),
+        Some(c) if byte && !c.is_ascii() => return Err(invalid(res, Some(c))),
+        Some(c) => res.push(c),
+        None => return Err(ParseError::UnterminatedChar),
+    }
+    match chars.next() {
+        Some('\'') => {
+            res.push('\'');
+            Ok(res)
+        }
+        _ => Err(ParseError::UnterminatedChar),
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::{Position, Span};
+
+    /// Returns the span from `start` to `end`, given as `(offset, line, column)`.
+    fn span(start: (usize, usize, usize), end: (usize, usize, usize)) -> Option<Span> {
+        let position = |(offset, line, column)| Position {
+            offset,
+            line,
+            column,
+        };
+        Some(Span {
+            start: position(start),
+            end: position(end),
+        })
+    }
+
     #[test]
     fn errors() {
         use crate::{
-            parser::{parse, ParseError},
+            parser::{parse, ParseError, Sexp},
             Error,
         };
         use alloc::string::String;
@@ -253,28 +693,30 @@ mod tests {
         assert_eq!(
             res.next().unwrap().err().unwrap(),
             Error {
-                lineno: Some(1),
+                span: span((0, 1, 1), (1, 1, 2)),
                 kind: ParseError::AtomOutsideList(String::from("a"))
             }
         );
         assert_eq!(
             res.next().unwrap().err().unwrap(),
             Error {
-                lineno: Some(2),
+                span: span((2, 2, 1), (3, 2, 2)),
                 kind: ParseError::Unexpected('\\')
             }
         );
         assert_eq!(
             res.next().unwrap().err().unwrap(),
             Error {
-                lineno: Some(3),
+                span: span((5, 3, 2), (6, 3, 3)),
                 kind: ParseError::WrongClose('(', ']')
             }
         );
+        // The completed `<>` comes before the error
+        assert!(matches!(res.next(), Some(Ok(Sexp::Generics(..)))));
         assert_eq!(
             res.next().unwrap().err().unwrap(),
             Error {
-                lineno: Some(4),
+                span: span((9, 4, 3), (10, 4, 4)),
                 kind: ParseError::TooMuch('>')
             }
         );
@@ -291,9 +733,244 @@ mod tests {
         assert_eq!(
             res.next().unwrap().err().unwrap(),
             Error {
-                lineno: None,
+                span: span((0, 1, 1), (1, 1, 2)),
                 kind: ParseError::Missing(')')
             }
         );
     }
+
+    #[test]
+    fn recovery() {
+        use crate::{
+            parser::{parse, ParseError, Sexp},
+            Error,
+        };
+
+        let mut res = parse(
+            "(f \\ \"x
+  (g)]\"
+(h)
+(i ]
+  j)
+
+(k)",
+        );
+        assert_eq!(
+            res.next().unwrap().err().unwrap(),
+            Error {
+                span: span((3, 1, 4), (4, 1, 5)),
+                kind: ParseError::Unexpected('\\')
+            }
+        );
+        assert!(matches!(res.next(), Some(Ok(Sexp::List(l, _))) if l.len() == 1));
+        assert_eq!(
+            res.next().unwrap().err().unwrap(),
+            Error {
+                span: span((23, 4, 4), (24, 4, 5)),
+                kind: ParseError::WrongClose('(', ']')
+            }
+        );
+        assert!(matches!(res.next(), Some(Ok(Sexp::List(l, _))) if l.len() == 1));
+        assert!(res.next().is_none());
+    }
+
+    #[test]
+    fn recovery_until_end() {
+        use crate::{
+            parser::{parse, ParseError, Sexp},
+            Error,
+        };
+        use alloc::string::String;
+
+        let mut res = parse("(g\n  (f '')\n)\n(h)\n(f '')");
+        assert_eq!(
+            res.next().unwrap().err().unwrap(),
+            Error {
+                span: span((8, 2, 6), (10, 2, 8)),
+                kind: ParseError::InvalidChar(String::from("''"))
+            }
+        );
+        // Neither the closing parenthesis of the erroneous form, nor the end of the input inside
+        // another one, are reported
+        assert!(matches!(res.next(), Some(Ok(Sexp::List(l, _))) if l.len() == 1));
+        assert_eq!(
+            res.next().unwrap().err().unwrap(),
+            Error {
+                span: span((21, 5, 4), (23, 5, 6)),
+                kind: ParseError::InvalidChar(String::from("''"))
+            }
+        );
+        assert!(res.next().is_none());
+    }
+
+    #[test]
+    fn recovery_after_forms() {
+        use crate::{
+            parser::{parse, ParseError, Sexp},
+            Error,
+        };
+
+        // The forms completed before an error on the same line come first
+        let fn_named = |res: Option<Result<Sexp, _>>, name: &str| {
+            assert!(matches!(res, Some(Ok(Sexp::List(l, _)))
+                if matches!(&l[1], Sexp::Atom { val, .. } if val == name)));
+        };
+        let unexpected = Error {
+            span: span((16, 1, 17), (17, 1, 18)),
+            kind: ParseError::Unexpected('\\'),
+        };
+
+        let mut res = parse("(fn a ()) (fn b \\\n(fn c ())");
+        fn_named(res.next(), "a");
+        assert_eq!(res.next().unwrap().err().unwrap(), unexpected);
+        fn_named(res.next(), "c");
+        assert!(res.next().is_none());
+
+        let mut res = parse("(fn a ()) (fn b \\");
+        fn_named(res.next(), "a");
+        assert_eq!(res.next().unwrap().err().unwrap(), unexpected);
+        assert!(res.next().is_none());
+
+        let mut res = parse("(fn a ()) (fn b");
+        fn_named(res.next(), "a");
+        assert_eq!(
+            res.next().unwrap().err().unwrap(),
+            Error {
+                span: span((10, 1, 11), (11, 1, 12)),
+                kind: ParseError::Missing(')')
+            }
+        );
+        assert!(res.next().is_none());
+    }
+
+    #[test]
+    fn crlf_offsets() {
+        use crate::parser::{parse, parse_lines};
+
+        // `\r\n` terminators are counted, but not parsed
+        let mut res = parse("(f)\r\n(g)");
+        assert_eq!(
+            res.next().unwrap().unwrap().span(),
+            span((0, 1, 1), (3, 1, 4)).unwrap()
+        );
+        assert_eq!(
+            res.next().unwrap().unwrap().span(),
+            span((5, 2, 1), (8, 2, 4)).unwrap()
+        );
+
+        // Stripped lines are assumed to end with `\n`
+        let mut res = parse_lines(["(f)", "(g)"].into_iter());
+        assert_eq!(
+            res.nth(1).unwrap().unwrap().span(),
+            span((4, 2, 1), (7, 2, 4)).unwrap()
+        );
+    }
+
+    #[test]
+    fn char_errors() {
+        use crate::{
+            parser::{parse, ParseError},
+            Error,
+        };
+        use alloc::string::String;
+
+        let mut res = parse("(f '(x)\n(f '')\n(f '\\q')\n(f b'é')\n(f '\\u{D800}')");
+        assert_eq!(
+            res.next().unwrap().err().unwrap(),
+            Error {
+                span: span((3, 1, 4), (4, 1, 5)),
+                kind: ParseError::UnterminatedChar
+            }
+        );
+        for (start, end, literal) in [
+            ((11, 2, 4), (13, 2, 6), "''"),
+            ((18, 3, 4), (21, 3, 7), "'\\q"),
+            ((28, 4, 5), (31, 4, 7), "'é"),
+            ((37, 5, 4), (46, 5, 13), "'\\u{D800}"),
+        ] {
+            assert_eq!(
+                res.next().unwrap().err().unwrap(),
+                Error {
+                    span: span(start, end),
+                    kind: ParseError::InvalidChar(String::from(literal))
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn multi_line_strings() {
+        use crate::{
+            parser::{parse, ParseError, Sexp},
+            Error,
+        };
+
+        let mut res = parse("(f\n  \"a\n b\" c)\n(g r#\"\n\"#)\n(h \"");
+        match res.next().unwrap().unwrap() {
+            Sexp::List(l, list_span) => {
+                assert_eq!(Some(list_span), span((0, 1, 1), (14, 3, 7)));
+                match &l[..] {
+                    [_, Sexp::Atom {
+                        val: a,
+                        span: a_span,
+                    }, Sexp::Atom {
+                        val: c,
+                        span: c_span,
+                    }] => {
+                        assert_eq!(a, "\"a\n b\"");
+                        assert_eq!(Some(*a_span), span((5, 2, 3), (11, 3, 4)));
+                        assert_eq!(c, "c");
+                        assert_eq!(Some(*c_span), span((12, 3, 5), (13, 3, 6)));
+                    }
+                    l => panic!("Unexpected {:?}", l),
+                }
+            }
+            e => panic!("Unexpected {:?}", e),
+        }
+        match res.next().unwrap().unwrap() {
+            Sexp::List(l, _) => {
+                assert!(matches!(&l[1], Sexp::Atom { val, .. } if val == "r#\"\n\"#"));
+                assert_eq!(Some(l[1].span()), span((18, 4, 4), (24, 5, 3)));
+            }
+            e => panic!("Unexpected {:?}", e),
+        }
+        assert_eq!(
+            res.next().unwrap().err().unwrap(),
+            Error {
+                span: span((29, 6, 4), (30, 6, 5)),
+                kind: ParseError::Missing('"')
+            }
+        );
+        assert!(res.next().is_none());
+    }
+
+    #[test]
+    fn comments() {
+        use crate::{
+            parser::{parse, ParseError, Sexp},
+            Error,
+        };
+
+        let mut res = parse("(f #| a |# b #;c d)\n#; e\n(g #;)\n#| #| |#");
+        match res.next().unwrap().unwrap() {
+            Sexp::List(l, _) => assert!(matches!(
+                &l[..],
+                [Sexp::Atom { val: f, .. }, Sexp::Atom { val: b, .. }, Sexp::Atom { val: d, .. }]
+                    if f == "f" && b == "b" && d == "d"
+            )),
+            e => panic!("Unexpected {:?}", e),
+        }
+        match res.next().unwrap().unwrap() {
+            Sexp::List(l, _) => assert_eq!(l.len(), 1),
+            e => panic!("Unexpected {:?}", e),
+        }
+        assert_eq!(
+            res.next().unwrap().err().unwrap(),
+            Error {
+                span: span((32, 4, 1), (34, 4, 3)),
+                kind: ParseError::UnterminatedComment
+            }
+        );
+        assert!(res.next().is_none());
+    }
 }
@@ -1,6 +1,10 @@
-use alloc::string::{String, ToString};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
 use core::{
     fmt::{self, Display},
+    iter::Peekable,
     str::FromStr,
 };
 use itertools::Itertools;
@@ -8,12 +12,14 @@ use proc_macro2::*;
 
 use crate::{parser::Sexp, Error};
 
+mod attr;
 mod flow;
 mod r#fn;
 mod list;
 mod macros;
 mod ops;
 mod types;
+pub(crate) use attr::doc_comment;
 use macros::*;
 
 /// An error occurred during generating Rust code.
@@ -39,6 +45,11 @@ pub enum RustifyError {
     TooMuchArguments(String),
     /// Match condition malformed.
     ExpectedMatchCondition,
+    /// Input that cannot be represented in Rust code.
+    Unsupported(
+        /// Description of the unsupported input.
+        String,
+    ),
 }
 
 impl Display for RustifyError {
@@ -56,6 +67,7 @@ impl Display for RustifyError {
             Self::ExpectedMatchCondition => {
                 write!(f, "Expected list (condition value) in match body")
             }
+            Self::Unsupported(x) => write!(f, "Unsupported {}", x),
         }
     }
 }
@@ -69,10 +81,10 @@ pub type Result = core::result::Result<TokenStream, Error<RustifyError>>;
 /// use srs::rustify;
 /// # let parsed_sexp = srs::parse("(f x)").next().unwrap().unwrap();
 /// # macro_rules! println {
-/// #   ("{}", $v: tt) => { assert_eq!("f (x) ;", $v.to_string()); }
+/// #   ("{}", $v: tt) => { assert_eq!("f (x) ;", $v.to_string()) }
 /// # }
 /// # macro_rules! eprintln {
-/// #   ($($e: tt) *) => { panic!($($e)*); }
+/// #   ($($e: tt) *) => { panic!($($e)*) }
 /// # }
 ///
 /// match rustify(&parsed_sexp) {
@@ -87,24 +99,24 @@ pub fn rustify(exp: &Sexp) -> Result {
 
 fn exp_to_token_stream(exp: &Sexp, statement: bool, precedence: i8) -> Result {
     match exp {
-        Sexp::Atom { val, lineno } => match TokenStream::from_str(val) {
+        Sexp::Atom { val, span } => match TokenStream::from_str(val) {
             Ok(val) => Ok(val),
             Err(e) => Err(Error {
-                lineno: Some(*lineno),
+                span: Some(*span),
                 kind: RustifyError::AtomParseError(val.to_string(), e),
             }),
         },
-        Sexp::Array(a) => Ok(token_stream![Group(
+        Sexp::Array(a, _) => Ok(token_stream![Group(
             Delimiter::Bracket,
             interspere_token_stream!(a)?,
         )]),
-        Sexp::Generics(a) => {
-            let mut res = token_stream![Punct('<', Spacing::Joint)];
-            res.extend(interspere_token_stream!(a)?);
-            res.extend(token_stream![Punct('>', Spacing::Alone)]);
-            Ok(res)
+        Sexp::Generics(a, _) => types::generics_to_token_stream(a),
+        Sexp::List(l, _) => {
+            list::list_to_token_stream(l.iter(), statement, precedence, Container::Module)
         }
-        Sexp::List(l) => list::list_to_token_stream(l.iter(), statement, precedence),
+        Sexp::Comment { val, .. } if statement => Ok(attr::comment_to_token_stream(val)),
+        // Attributes are not allowed in expressions
+        Sexp::Comment { .. } => Ok(token_stream![]),
     }
 }
 
@@ -113,7 +125,7 @@ fn call_to_token_stream<'a>(
     args: impl Iterator<Item = &'a Sexp>,
     statement: bool,
 ) -> Result {
-    let mut res = exp_to_token_stream(name, false, i8::MAX)?;
+    let mut res = exp_to_token_stream(name, false, ops::precedence("()", false))?;
     let mut args = args.peekable();
     res.extend(match args.peek() {
         // Construct struct
@@ -121,18 +133,18 @@ fn call_to_token_stream<'a>(
             let mut body = token_stream![];
             while let Some(a) = args.next() {
                 match a {
-                    Sexp::Atom { val, .. } if val.starts_with(':') => {
-                        body.extend(TokenStream::from_str(&val[1..]));
+                    Sexp::Atom { val, span } if val.starts_with(':') => {
+                        body.extend(ident_to_token_stream(&val[1..], *span)?);
                         if let Some(a) = args.peek() {
                             body.extend(token_stream![Punct(match a {
                                 Sexp::Atom { val, .. } if val.starts_with(&[':', '.'][..]) => ',',
-                                Sexp::List(v) if matches!(v.first(), Some(Sexp::Atom { val, .. }) if val.starts_with('.')) => ',',
+                                Sexp::List(v, _) if matches!(v.first(), Some(Sexp::Atom { val, .. }) if val == "..") => ',',
                                 _ => ':'
                             }, Spacing::Alone)])
                         }
                     }
                     _ => {
-                        body.extend(exp_to_token_stream(a, false, i8::MAX));
+                        body.extend(exp_to_token_stream(a, false, i8::MAX)?);
                         if args.peek().is_some() {
                             body.extend(token_stream!(Punct(',', Spacing::Alone)))
                         }
@@ -155,6 +167,21 @@ fn call_to_token_stream<'a>(
     Ok(res)
 }
 
+/// Writes a tuple (e.g. `(, a b)`).
+fn tuple_to_token_stream<'a>(l: impl Iterator<Item = &'a Sexp>, statement: bool) -> Result {
+    let l = l.collect::<Vec<_>>();
+    let single = l.len() == 1;
+    let mut body = interspere_token_stream!(l)?;
+    if single {
+        body.extend(token_stream![Punct(',', Spacing::Alone)]);
+    }
+    let mut res = token_stream![Group(Delimiter::Parenthesis, body)];
+    if statement {
+        res.extend(token_stream![Punct(';', Spacing::Alone)])
+    }
+    Ok(res)
+}
+
 /// Writes the body of a block
 /// l: expressions
 /// returns: returns last expression?
@@ -162,7 +189,8 @@ fn block_to_token_stream<'a>(l: impl Iterator<Item = &'a Sexp>, returns: bool) -
     Ok(token_stream![Group(
         Delimiter::Brace,
         TokenStream::from_iter(
-            l.peekable()
+            l.filter(|x| !is_plain_comment(x))
+                .peekable()
                 .batching(|it| it.next().map(|x| exp_to_token_stream(
                     x,
                     !returns || it.peek().is_some(),
@@ -173,24 +201,214 @@ fn block_to_token_stream<'a>(l: impl Iterator<Item = &'a Sexp>, returns: bool) -
     )])
 }
 
-fn path_to_token_stream(path: &Sexp, lineno: usize) -> Result {
+/// Block containing an item, which changes how some items are written.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Container {
+    /// A module (or the top-level), where items are free.
+    Module,
+    /// An `impl`, where items are associated.
+    Impl,
+    /// A `trait`, where items are associated and functions can lack the body.
+    Trait,
+}
+
+/// Writes items (e.g. of a `mod`, an `impl` or a `trait`) surrounded by braces.
+fn items_to_token_stream<'a>(l: impl Iterator<Item = &'a Sexp>, container: Container) -> Result {
+    Ok(token_stream![Group(
+        Delimiter::Brace,
+        l.map(|item| match item {
+            Sexp::List(l, _) => list::list_to_token_stream(l.iter(), true, i8::MAX, container),
+            _ => exp_to_token_stream(item, true, i8::MAX),
+        })
+        .collect::<Result>()?
+    )])
+}
+
+/// Returns the arguments of `exp` if it is the form `(name args...)`.
+fn form_args<'a>(exp: &'a Sexp, name: &str) -> Option<&'a [Sexp]> {
+    match exp {
+        Sexp::List(l, _) => match l.first() {
+            Some(Sexp::Atom { val, .. }) if val == name => Some(&l[1..]),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Writes a binding, as the parameters of a function.
+///
+/// A binding can be a pattern, `(mut binding)`, `(:name Type)` or `(: pattern Type)`.
+fn binding_to_token_stream(exp: &Sexp) -> Result {
+    match exp {
+        Sexp::List(l, _) => match l.first() {
+            Some(Sexp::Atom { val, span })
+                if val == "mut" || (val.starts_with(':') && val != "::") =>
+            {
+                binding_items_to_token_stream(l, *span)
+            }
+            _ => exp_to_token_stream(exp, false, i8::MAX),
+        },
+        _ => exp_to_token_stream(exp, false, i8::MAX),
+    }
+}
+
+fn binding_items_to_token_stream(l: &[Sexp], span: crate::Span) -> Result {
+    match l {
+        [exp] => binding_to_token_stream(exp),
+        [Sexp::Atom { val, .. }, rest @ ..] if val == "mut" => {
+            let mut res = token_stream![Ident("mut", Span::call_site())];
+            res.extend(binding_items_to_token_stream(rest, span)?);
+            Ok(res)
+        }
+        [Sexp::Atom { val, span }, pat, ty @ ..] if val == ":" => {
+            let mut res = exp_to_token_stream(pat, false, i8::MAX)?;
+            res.extend(token_stream![Punct(':', Spacing::Alone)]);
+            res.extend(types::types_to_token_stream(ty, *span)?);
+            Ok(res)
+        }
+        [Sexp::Atom { val, span }, ty @ ..] if val.starts_with(':') && val != "::" => {
+            let mut res = ident_to_token_stream(&val[1..], *span)?;
+            res.extend(token_stream![Punct(':', Spacing::Alone)]);
+            res.extend(types::types_to_token_stream(ty, *span)?);
+            Ok(res)
+        }
+        _ => Err(Error {
+            span: Some(span),
+            kind: RustifyError::MissingArguments("binding".into()),
+        }),
+    }
+}
+
+/// Writes the identifier `val` (e.g. a name in a definition), occurred at `span`.
+fn ident_to_token_stream(val: &str, span: crate::Span) -> Result {
+    let res = TokenStream::from_str(val).map_err(|e| Error {
+        span: Some(span),
+        kind: RustifyError::AtomParseError(val.to_string(), e),
+    })?;
+    match res.clone().into_iter().exactly_one() {
+        Ok(TokenTree::Ident(_)) => Ok(res),
+        _ => Err(Error {
+            span: Some(span),
+            kind: RustifyError::Unsupported(format!("identifier `{}`", val)),
+        }),
+    }
+}
+
+/// Checks if `exp` is a comment, other than a doc comment (e.g. `;; note`), producing no code.
+fn is_plain_comment(exp: &Sexp) -> bool {
+    matches!(exp, Sexp::Comment { val, .. } if attr::doc_comment(val).is_none())
+}
+
+/// Checks if `exp` is a label or a lifetime (e.g. `'outer`), rather than a character (e.g. `'a'`).
+fn is_label(exp: &Sexp) -> bool {
+    matches!(exp, Sexp::Atom { val, .. } if val.starts_with('\'') && !val.ends_with('\''))
+}
+
+fn path_to_token_stream(path: &Sexp) -> Result {
     match path {
-        Sexp::Atom { val, .. } => Ok(TokenStream::from_str(val).map_err(|e| Error {
-            lineno: Some(lineno),
+        Sexp::Atom { val, span } => Ok(TokenStream::from_str(val).map_err(|e| Error {
+            span: Some(*span),
             kind: RustifyError::AtomParseError(val.to_string(), e),
         })?),
-        Sexp::List(l) => match l.first() {
-            Some(Sexp::Atom { val, lineno }) if val == "::" => {
-                interspere_token_stream!(l[1..].iter(), "::", |p| path_to_token_stream(p, *lineno))
+        Sexp::List(l, _) => match l.first() {
+            Some(Sexp::Atom { val, .. }) if val == "::" => {
+                interspere_token_stream!(l[1..].iter(), "::", path_to_token_stream)
             }
             _ => Ok(token_stream!(Group(
                 Delimiter::Brace,
-                interspere_token_stream!(l, ',', |p| path_to_token_stream(p, lineno))?
+                interspere_token_stream!(l, ',', path_to_token_stream)?
             ))),
         },
         _ => Err(Error {
-            lineno: Some(lineno),
+            span: Some(path.span()),
             kind: RustifyError::UnexpectedFunctionName(rustify(path)?.to_string()),
         }),
     }
 }
+
+/// Writes the `pub` visibility, followed by its restriction (e.g. `(crate)`, `(in a::b)`), if any.
+fn vis_to_token_stream<'a>(l: &mut Peekable<impl Iterator<Item = &'a Sexp>>) -> Result {
+    let mut res = token_stream![Ident("pub", Span::call_site())];
+    if let Some(Sexp::List(r, _)) = l.next_if(|x| is_vis_restriction(x)) {
+        let mut restriction = token_stream![];
+        if let [Sexp::Atom { val, .. }, path] = &r[..] {
+            restriction.extend(token_stream![Ident(val, Span::call_site())]);
+            restriction.extend(path_to_token_stream(path)?);
+        } else {
+            restriction.extend(interspere_token_stream!(r)?);
+        }
+        res.extend(token_stream![Group(Delimiter::Parenthesis, restriction)]);
+    }
+    Ok(res)
+}
+
+/// Checks if `exp` is a visibility restriction (`(crate)`, `(self)`, `(super)` or `(in path)`).
+fn is_vis_restriction(exp: &Sexp) -> bool {
+    match exp {
+        Sexp::List(l, _) => match &l[..] {
+            [Sexp::Atom { val, .. }] => matches!(val.as_str(), "crate" | "self" | "super"),
+            [Sexp::Atom { val, .. }, _] => val == "in",
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Writes a module, inline (e.g. `(mod a items...)`) or declared in its own file (e.g. `(mod a)`).
+fn mod_to_token_stream<'a>(mut l: impl Iterator<Item = &'a Sexp>, span: crate::Span) -> Result {
+    let mut res = token_stream![Ident("mod", Span::call_site())];
+    match l.next() {
+        Some(name @ Sexp::Atom { .. }) => res.extend(exp_to_token_stream(name, false, i8::MAX)?),
+        _ => {
+            return Err(Error {
+                span: Some(span),
+                kind: RustifyError::MissingArguments("mod".into()),
+            })
+        }
+    }
+    let mut l = l.peekable();
+    if l.peek().is_none() {
+        res.extend(token_stream![Punct(';', Spacing::Alone)]);
+    } else {
+        res.extend(items_to_token_stream(l, Container::Module)?);
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    /// Returns the error kinds occurred rustifying each form of `input`.
+    fn error_kinds(input: &str) -> Vec<RustifyError> {
+        parse(input)
+            .map(|x| rustify(&x.unwrap()).unwrap_err().kind)
+            .collect()
+    }
+
+    #[test]
+    fn errors() {
+        let kinds = error_kinds(
+            "(fn main () (match x (1 (f /*))))\n\
+             (fn main () (return (f /*)))\n\
+             (fn main () (S :a (f /*)))\n\
+             (fn 1a () ())\n\
+             (struct S :a-b u8)\n\
+             (fn main () (let (:a-b u8) 1))\n\
+             (fn f<(:T-U Clone)> ())",
+        );
+        assert!(matches!(
+            &kinds[..],
+            [
+                RustifyError::AtomParseError(..),
+                RustifyError::AtomParseError(..),
+                RustifyError::AtomParseError(..),
+                RustifyError::Unsupported(_),
+                RustifyError::Unsupported(_),
+                RustifyError::Unsupported(_),
+                RustifyError::Unsupported(_),
+            ]
+        ));
+    }
+}
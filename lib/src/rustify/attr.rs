@@ -0,0 +1,97 @@
+use super::*;
+use core::iter::Peekable;
+
+/// Writes an attribute from the content of its brackets, as an inner one (`#![...]`) if `inner`.
+///
+/// The content can be `[name]`, `[name = value]` or `[name args...]`
+/// (e.g. `[derive Debug Clone]` becomes `#[derive(Debug, Clone)]`).
+pub fn attr_to_token_stream(inner: bool, attr: &[Sexp], span: crate::Span) -> Result {
+    let mut res = token_stream![Punct('#', Spacing::Alone)];
+    if inner {
+        res.extend(token_stream![Punct('!', Spacing::Alone)]);
+    }
+    let mut body = match attr.first() {
+        Some(name) => exp_to_token_stream(name, false, i8::MAX)?,
+        None => {
+            return Err(Error {
+                span: Some(span),
+                kind: RustifyError::MissingArguments("attribute".into()),
+            })
+        }
+    };
+    match &attr[1..] {
+        [] => {}
+        [Sexp::Atom { val, .. }, value] if val == "=" => {
+            body.extend(token_stream![Punct('=', Spacing::Alone)]);
+            body.extend(exp_to_token_stream(value, false, i8::MAX)?);
+        }
+        args => body.extend(token_stream![Group(
+            Delimiter::Parenthesis,
+            interspere_token_stream!(args)?
+        )]),
+    }
+    res.extend(token_stream![Group(Delimiter::Bracket, body)]);
+    Ok(res)
+}
+
+/// Checks if `exp` starts an attribute (`#` or `#!`), or is a comment.
+pub fn is_attr(exp: &Sexp) -> bool {
+    matches!(exp, Sexp::Atom { val, .. } if val == "#" || val == "#!")
+        || matches!(exp, Sexp::Comment { .. })
+}
+
+/// Returns the text of a doc comment, and whether it is an inner one (e.g. `;;; outer`, `;;;!
+/// inner`), or `None` for the other comments (e.g. `;; plain`, `;;;; plain`).
+pub fn doc_comment(comment: &str) -> Option<(bool, &str)> {
+    match comment.strip_prefix(";;;") {
+        Some(doc) if !doc.starts_with(';') => Some(match doc.strip_prefix('!') {
+            Some(doc) => (true, doc),
+            None => (false, doc),
+        }),
+        _ => None,
+    }
+}
+
+/// Writes a doc comment as a documentation attribute (e.g. `;;; doc` becomes `#[doc = " doc"]`).
+///
+/// Other comments produce no code, since a token stream cannot contain comments.
+pub fn comment_to_token_stream(comment: &str) -> TokenStream {
+    match doc_comment(comment) {
+        Some((inner, doc)) => {
+            let mut res = token_stream![Punct('#', Spacing::Alone)];
+            if inner {
+                res.extend(token_stream![Punct('!', Spacing::Alone)]);
+            }
+            let mut body =
+                token_stream![Ident("doc", Span::call_site()), Punct('=', Spacing::Alone)];
+            body.extend([TokenTree::Literal(Literal::string(doc))]);
+            res.extend(token_stream![Group(Delimiter::Bracket, body)]);
+            res
+        }
+        None => token_stream![],
+    }
+}
+
+/// Writes all the consecutive attributes (e.g. `#[derive Debug] #[repr C]`) at the beginning of
+/// `l`.
+pub fn attrs_to_token_stream<'a>(l: &mut Peekable<impl Iterator<Item = &'a Sexp>>) -> Result {
+    let mut res = token_stream![];
+    while let Some(attr) = l.next_if(|x| is_attr(x)) {
+        match attr {
+            Sexp::Atom { val, span } => match l.next() {
+                Some(Sexp::Array(attr, _)) => {
+                    res.extend(attr_to_token_stream(val == "#!", attr, *span)?)
+                }
+                _ => {
+                    return Err(Error {
+                        span: Some(*span),
+                        kind: RustifyError::MissingArguments("attribute".into()),
+                    })
+                }
+            },
+            Sexp::Comment { val, .. } => res.extend(comment_to_token_stream(val)),
+            _ => (),
+        }
+    }
+    Ok(res)
+}
@@ -1,15 +1,16 @@
 use super::*;
+use core::iter::Peekable;
 use ops::PunctAsTokenStream;
 
 pub fn match_to_token_stream<'a>(
     mut l: impl Iterator<Item = &'a Sexp>,
-    lineno: usize,
+    span: crate::Span,
     statement: bool,
 ) -> Result {
     let mut res = token_stream![Ident("match", Span::call_site())];
     res.extend(exp_to_token_stream(
         l.next().ok_or(Error {
-            lineno: Some(lineno),
+            span: Some(span),
             kind: RustifyError::MissingArguments("match".into()),
         })?,
         false,
@@ -19,26 +20,31 @@ pub fn match_to_token_stream<'a>(
     res.extend(token_stream![Group(
         Delimiter::Brace,
         TokenStream::from_iter(
-            l.map(|m| if let Sexp::List(m) = m {
-                let mut res = exp_to_token_stream(
-                    m.get(0).ok_or(Error {
-                        lineno: Some(lineno),
+            l.map(|m| if let Sexp::List(m, _) = m {
+                let mut m = m.iter().peekable();
+                let mut res = attr::attrs_to_token_stream(&mut m)?;
+                res.extend(exp_to_token_stream(
+                    m.next().ok_or(Error {
+                        span: Some(span),
                         kind: RustifyError::ExpectedMatchCondition,
                     })?,
                     false,
                     i8::MAX,
-                )?;
+                )?);
                 res.extend("=>".punct_as_token_stream());
-                if m.len() == 2 {
-                    res.extend(exp_to_token_stream(m.get(1).unwrap(), false, i8::MAX));
+                let body = m.collect::<Vec<_>>();
+                if let [exp] = body[..] {
+                    res.extend(exp_to_token_stream(exp, false, i8::MAX)?);
                     res.extend(','.punct_as_token_stream());
                 } else {
-                    res.extend(block_to_token_stream(m[1..].iter(), !statement)?)
+                    res.extend(block_to_token_stream(body.into_iter(), !statement)?)
                 }
                 Ok(res)
+            } else if let Sexp::Comment { val, .. } = m {
+                Ok(attr::comment_to_token_stream(val))
             } else {
                 Err(Error {
-                    lineno: Some(lineno),
+                    span: Some(span),
                     kind: RustifyError::ExpectedMatchCondition,
                 })
             })
@@ -52,3 +58,169 @@ pub fn match_to_token_stream<'a>(
 
     Ok(res)
 }
+
+pub fn if_to_token_stream<'a>(
+    l: impl Iterator<Item = &'a Sexp>,
+    span: crate::Span,
+    statement: bool,
+) -> Result {
+    let mut res = if_else_to_token_stream(l, span, !statement)?;
+
+    if statement {
+        res.extend(token_stream![Punct(';', Spacing::Alone)]);
+    }
+
+    Ok(res)
+}
+
+/// Writes an `if` expression, chaining `else if` when the else branch is an `if` form.
+/// returns: branches return their last expression?
+fn if_else_to_token_stream<'a>(
+    mut l: impl Iterator<Item = &'a Sexp>,
+    span: crate::Span,
+    returns: bool,
+) -> Result {
+    let mut res = token_stream![Ident("if", Span::call_site())];
+    res.extend(cond_to_token_stream(l.next().ok_or(Error {
+        span: Some(span),
+        kind: RustifyError::MissingArguments("if".into()),
+    })?)?);
+    res.extend(branch_to_token_stream(
+        l.next().ok_or(Error {
+            span: Some(span),
+            kind: RustifyError::MissingArguments("if".into()),
+        })?,
+        returns,
+    )?);
+
+    if let Some(else_branch) = l.next() {
+        res.extend(token_stream![Ident("else", Span::call_site())]);
+        res.extend(match form_args(else_branch, "if") {
+            Some(else_if) => if_else_to_token_stream(else_if.iter(), span, returns)?,
+            None => branch_to_token_stream(else_branch, returns)?,
+        });
+    }
+
+    if l.next().is_some() {
+        return Err(Error {
+            span: Some(span),
+            kind: RustifyError::TooMuchArguments("if".into()),
+        });
+    }
+
+    Ok(res)
+}
+
+/// Writes a branch as a block, unwrapping `(do ...)` forms.
+fn branch_to_token_stream(branch: &Sexp, returns: bool) -> Result {
+    match form_args(branch, "do") {
+        Some(body) => block_to_token_stream(body.iter(), returns),
+        None => block_to_token_stream(core::iter::once(branch), returns),
+    }
+}
+
+/// Writes the condition of `if` and `while`, supporting `(let pattern value)`.
+fn cond_to_token_stream(cond: &Sexp) -> Result {
+    match form_args(cond, "let") {
+        Some([pat, val]) => {
+            let mut res = token_stream![Ident("let", Span::call_site())];
+            res.extend(exp_to_token_stream(pat, false, i8::MAX)?);
+            res.extend(token_stream![Punct('=', Spacing::Alone)]);
+            res.extend(exp_to_token_stream(val, false, i8::MAX)?);
+            Ok(res)
+        }
+        _ => exp_to_token_stream(cond, false, i8::MAX),
+    }
+}
+
+/// Writes the optional loop label (e.g. `'outer`) followed by `keyword`.
+fn label_to_token_stream<'a>(
+    l: &mut Peekable<impl Iterator<Item = &'a Sexp>>,
+    keyword: &str,
+) -> Result {
+    let mut res = token_stream![];
+    if let Some(label) = l.next_if(|x| is_label(x)) {
+        res.extend(exp_to_token_stream(label, false, i8::MAX)?);
+        res.extend(token_stream![Punct(':', Spacing::Alone)]);
+    }
+    res.extend(token_stream![Ident(keyword, Span::call_site())]);
+    Ok(res)
+}
+
+pub fn loop_to_token_stream<'a>(l: impl Iterator<Item = &'a Sexp>, statement: bool) -> Result {
+    let mut l = l.peekable();
+    let mut res = label_to_token_stream(&mut l, "loop")?;
+    res.extend(block_to_token_stream(l, !statement)?);
+    Ok(res)
+}
+
+pub fn for_to_token_stream<'a>(l: impl Iterator<Item = &'a Sexp>, span: crate::Span) -> Result {
+    let mut l = l.peekable();
+    let mut res = label_to_token_stream(&mut l, "for")?;
+    let mut arg = || {
+        exp_to_token_stream(
+            l.next().ok_or(Error {
+                span: Some(span),
+                kind: RustifyError::MissingArguments("for".into()),
+            })?,
+            false,
+            i8::MAX,
+        )
+    };
+    res.extend(arg()?);
+    res.extend(token_stream![Ident("in", Span::call_site())]);
+    res.extend(arg()?);
+    res.extend(block_to_token_stream(l, false)?);
+    Ok(res)
+}
+
+pub fn while_to_token_stream<'a>(l: impl Iterator<Item = &'a Sexp>, span: crate::Span) -> Result {
+    let mut l = l.peekable();
+    let mut res = label_to_token_stream(&mut l, "while")?;
+    res.extend(cond_to_token_stream(l.next().ok_or(Error {
+        span: Some(span),
+        kind: RustifyError::MissingArguments("while".into()),
+    })?)?);
+    res.extend(block_to_token_stream(l, false)?);
+    Ok(res)
+}
+
+pub fn let_to_token_stream<'a>(
+    l: impl Iterator<Item = &'a Sexp>,
+    span: crate::Span,
+    statement: bool,
+) -> Result {
+    let mut l = l.peekable();
+    let mut res = token_stream![Ident("let", Span::call_site())];
+    if l.next_if(|x| matches!(x, Sexp::Atom { val, .. } if val == "mut"))
+        .is_some()
+    {
+        res.extend(token_stream![Ident("mut", Span::call_site())]);
+    }
+    res.extend(binding_to_token_stream(l.next().ok_or(Error {
+        span: Some(span),
+        kind: RustifyError::MissingArguments("let".into()),
+    })?)?);
+
+    if let Some(val) = l.next() {
+        res.extend(token_stream![Punct('=', Spacing::Alone)]);
+        res.extend(exp_to_token_stream(val, false, i8::MAX)?);
+        if l.next_if(|x| matches!(x, Sexp::Atom { val, .. } if val == "else"))
+            .is_some()
+        {
+            res.extend(token_stream![Ident("else", Span::call_site())]);
+            res.extend(block_to_token_stream(l, false)?);
+        } else if l.next().is_some() {
+            return Err(Error {
+                span: Some(span),
+                kind: RustifyError::TooMuchArguments("let".into()),
+            });
+        }
+    }
+
+    if statement {
+        res.extend(token_stream![Punct(';', Spacing::Alone)]);
+    }
+
+    Ok(res)
+}
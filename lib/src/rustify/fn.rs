@@ -1,34 +1,168 @@
 use super::*;
+use core::iter::Peekable;
+use ops::PunctAsTokenStream;
 
+/// Writes a function or a closure.
+/// signature: can the function lack the body (i.e. is it a trait item)?
 pub fn fn_to_token_stream<'a>(
-    mut l: impl Iterator<Item = &'a Sexp>,
-    _statement: bool,
-    lineno: usize,
+    l: impl Iterator<Item = &'a Sexp>,
+    statement: bool,
+    precedence: i8,
+    signature: bool,
+    span: crate::Span,
+) -> Result {
+    let mut l = l.peekable();
+    match l.peek() {
+        Some(Sexp::Atom { val, .. }) if val != "move" => {
+            named_fn_to_token_stream(l, signature, span)
+        }
+        Some(_) => closure_to_token_stream(l, statement, precedence, span),
+        None => Err(Error {
+            span: Some(span),
+            kind: RustifyError::MissingArguments("function definition".into()),
+        }),
+    }
+}
+
+fn named_fn_to_token_stream<'a>(
+    mut l: Peekable<impl Iterator<Item = &'a Sexp>>,
+    signature: bool,
+    span: crate::Span,
 ) -> Result {
     let mut res = token_stream![Ident("fn", Span::call_site())];
-    if let Sexp::Atom { val, lineno } = l.next().ok_or(Error {
-        lineno: Some(lineno),
-        kind: RustifyError::MissingArguments("function definition".into()),
-    })? {
-        res.extend(token_stream![
-            Ident(val, Span::call_site()),
-            Group(Delimiter::Parenthesis, token_stream![])
-        ]);
-        /* let args = loop {
-            match l.next().unwrap_or_else(|| {
-                panic!("Missing arguments for function {} on line {}", val, lineno)
-            }) {
-                Sexp::List(a) => break a,
-                g @ Sexp::Generics(_) => write!(f, "{}", g)?,
-                _ => panic!("Missing arguments for function {} on line {}", val, lineno),
+    let mut returns = false;
+    if let Some(Sexp::Atom {
+        val,
+        span: name_span,
+    }) = l.next()
+    {
+        res.extend(ident_to_token_stream(val, *name_span)?);
+        if let Some(Sexp::Generics(g, _)) = l.next_if(|x| matches!(x, Sexp::Generics(..))) {
+            res.extend(types::generics_to_token_stream(g)?);
+        }
+        match l.next() {
+            Some(Sexp::List(args, _)) => res.extend(token_stream![Group(
+                Delimiter::Parenthesis,
+                fn_params_to_token_stream(args)?
+            )]),
+            _ => {
+                return Err(Error {
+                    span: Some(span),
+                    kind: RustifyError::MissingArguments(format!("function {}", val)),
+                })
             }
-        }; */
+        }
+        if let Some(ret) = return_type_to_token_stream(&mut l, span)? {
+            res.extend(ret);
+            returns = true;
+        }
+        res.extend(types::where_clause_to_token_stream(&mut l)?);
+    }
+    if signature && l.peek().is_none() {
+        // Required trait method
+        res.extend(token_stream![Punct(';', Spacing::Alone)]);
     } else {
-        todo!();
-        // lambda
-        /* write!(f, "{}|", indent!())?;
-        write!(f, "| ") */
-    };
-    res.extend(block_to_token_stream(l, false)?);
+        res.extend(block_to_token_stream(l, returns)?);
+    }
     Ok(res)
 }
+
+fn closure_to_token_stream<'a>(
+    mut l: Peekable<impl Iterator<Item = &'a Sexp>>,
+    statement: bool,
+    precedence: i8,
+    span: crate::Span,
+) -> Result {
+    let mut res = token_stream![];
+    if l.next_if(|x| matches!(x, Sexp::Atom { val, .. } if val == "move"))
+        .is_some()
+    {
+        res.extend(token_stream![Ident("move", Span::call_site())]);
+    }
+    match l.next() {
+        Some(Sexp::List(args, _)) => {
+            res.extend('|'.punct_as_token_stream());
+            res.extend(interspere_token_stream!(
+                args,
+                ',',
+                binding_to_token_stream
+            )?);
+            res.extend('|'.punct_as_token_stream());
+        }
+        _ => {
+            return Err(Error {
+                span: Some(span),
+                kind: RustifyError::MissingArguments("closure".into()),
+            })
+        }
+    }
+    let ret = return_type_to_token_stream(&mut l, span)?;
+    let body = l.collect::<Vec<_>>();
+    match (ret, &body[..]) {
+        (None, [body]) => res.extend(exp_to_token_stream(body, false, i8::MAX)?),
+        // The body of a closure with explicit return type must be a block
+        (ret, _) => {
+            res.extend(ret);
+            res.extend(block_to_token_stream(body.into_iter(), true)?);
+        }
+    }
+
+    if ops::precedence("fn", false) > precedence {
+        res = token_stream![Group(Delimiter::Parenthesis, res)];
+    }
+
+    if statement {
+        res.extend(token_stream![Punct(';', Spacing::Alone)]);
+    }
+    Ok(res)
+}
+
+/// Writes comma separated parameters.
+///
+/// The `&`, `&mut` and `mut` prefixes are glued to the following parameter, allowing receivers
+/// like `&mut self`.
+fn fn_params_to_token_stream(l: &[Sexp]) -> Result {
+    // Comments cannot be attached to parameters
+    let mut l = l
+        .iter()
+        .filter(|x| !matches!(x, Sexp::Comment { .. }))
+        .peekable();
+    let mut res = token_stream![];
+    while let Some(param) = l.next() {
+        if let Sexp::Atom { val, .. } = param {
+            // Prefixes of a receiver (e.g. `&'a mut self`)
+            if (matches!(val.as_str(), "&" | "&mut" | "mut") || is_label(param))
+                && l.peek().is_some()
+            {
+                res.extend(exp_to_token_stream(param, false, i8::MAX)?);
+                continue;
+            }
+        }
+        res.extend(binding_to_token_stream(param)?);
+        if l.peek().is_some() {
+            res.extend(','.punct_as_token_stream());
+        }
+    }
+    Ok(res)
+}
+
+/// Writes the return type (e.g. `-> u8`), if any.
+fn return_type_to_token_stream<'a>(
+    l: &mut Peekable<impl Iterator<Item = &'a Sexp>>,
+    span: crate::Span,
+) -> core::result::Result<Option<TokenStream>, Error<RustifyError>> {
+    if l.next_if(|x| matches!(x, Sexp::Atom { val, .. } if val == "->"))
+        .is_none()
+    {
+        return Ok(None);
+    }
+    let mut res = "->".punct_as_token_stream();
+    res.extend(types::type_to_token_stream(
+        l.next().ok_or(Error {
+            span: Some(span),
+            kind: RustifyError::MissingArguments("->".into()),
+        })?,
+        l,
+    )?);
+    Ok(Some(res))
+}
@@ -1,18 +1,32 @@
 use super::*;
 
+/// Writes a list form.
+/// container: block containing the list, if it is an item
 pub fn list_to_token_stream<'a>(
-    mut l: impl Iterator<Item = &'a Sexp>,
+    l: impl Iterator<Item = &'a Sexp>,
     statement: bool,
     precedence: i8,
+    container: Container,
 ) -> Result {
-    // let mut l = l.iter().peekable();
+    let mut l = l.filter(|x| !is_plain_comment(x)).collect::<Vec<_>>();
+    // Outer doc comments documenting nothing (e.g. `(fn f () (g) ;;; doc)`)
+    while matches!(l.last(), Some(Sexp::Comment { val, .. })
+        if matches!(attr::doc_comment(val), Some((false, _))))
+    {
+        l.pop();
+    }
+    let comments = keeps_comments(&l);
+    let mut l = l
+        .into_iter()
+        .filter(|x| comments || !matches!(x, Sexp::Comment { .. }))
+        .peekable();
 
     let mut res = token_stream![];
 
     while let Some(exp) = l.next() {
         match exp {
-            Sexp::Atom { val, lineno } => {
-                let lineno = *lineno;
+            Sexp::Atom { val, span } => {
+                let span = *span;
                 match val.as_str() {
                     // Operators
                     "!" => {
@@ -20,7 +34,7 @@ pub fn list_to_token_stream<'a>(
                         let self_precedence = ops::precedence(val, true);
                         stream.extend(exp_to_token_stream(
                             l.next().ok_or(Error {
-                                lineno: Some(lineno),
+                                span: Some(span),
                                 kind: RustifyError::MissingOperand(val.to_string()),
                             })?,
                             false,
@@ -28,7 +42,7 @@ pub fn list_to_token_stream<'a>(
                         )?);
                         if l.next().is_some() {
                             return Err(Error {
-                                lineno: Some(lineno),
+                                span: Some(span),
                                 kind: RustifyError::TooMuchArguments(val.to_string()),
                             });
                         }
@@ -44,49 +58,145 @@ pub fn list_to_token_stream<'a>(
                     | "|=" | "&=" | "<<=" | ">>=" | "^=" | "=" | "==" | "!=" | "<" | "<=" | ">"
                     | ">=" | ".." | "as" => {
                         res.extend(ops::op_to_token_stream(
-                            val, l, lineno, statement, precedence,
+                            val, l, span, statement, precedence,
                         )?);
                         break;
                     }
 
+                    // Tuples
+                    "," => {
+                        res.extend(tuple_to_token_stream(l, statement)?);
+                        break;
+                    }
+
+                    // Binding modes
+                    "ref" | "mut" => {
+                        res.extend(token_stream![Ident(val, Span::call_site())]);
+                        if val == "ref"
+                            && l.next_if(|x| matches!(x, Sexp::Atom { val, .. } if val == "mut"))
+                                .is_some()
+                        {
+                            res.extend(token_stream![Ident("mut", Span::call_site())]);
+                        }
+                        res.extend(binding_to_token_stream(l.next().ok_or(Error {
+                            span: Some(span),
+                            kind: RustifyError::MissingArguments(val.to_string()),
+                        })?)?);
+                        if l.next().is_some() {
+                            return Err(Error {
+                                span: Some(span),
+                                kind: RustifyError::TooMuchArguments(val.to_string()),
+                            });
+                        }
+                        break;
+                    }
+
+                    // Attributes
+                    "#" | "#!" => match l.next() {
+                        Some(Sexp::Array(a, _)) => {
+                            res.extend(attr::attr_to_token_stream(val == "#!", a, span)?)
+                        }
+                        _ => {
+                            return Err(Error {
+                                span: Some(span),
+                                kind: RustifyError::MissingArguments("attribute".into()),
+                            })
+                        }
+                    },
+
                     // Public
-                    "pub" => res.extend(token_stream![Ident("pub", Span::call_site())]),
+                    "pub" => res.extend(vis_to_token_stream(&mut l)?),
+
+                    // Qualifiers
+                    "const" if !starts_fn(l.peek()) => {
+                        res.extend(types::const_to_token_stream(val, l, span)?);
+                        break;
+                    }
+                    "static" => {
+                        res.extend(types::const_to_token_stream(val, l, span)?);
+                        break;
+                    }
+                    "unsafe" if !starts_unsafe_item(l.peek()) => {
+                        res.extend(token_stream![Ident("unsafe", Span::call_site())]);
+                        res.extend(block_to_token_stream(l, !statement)?);
+                        break;
+                    }
+                    "const" | "async" | "unsafe" => {
+                        res.extend(token_stream![Ident(val, Span::call_site())])
+                    }
+                    "extern" => {
+                        res.extend(token_stream![Ident("extern", Span::call_site())]);
+                        if let Some(abi) = l.next_if(
+                            |x| matches!(x, Sexp::Atom { val, .. } if val.starts_with('"')),
+                        ) {
+                            res.extend(exp_to_token_stream(abi, false, i8::MAX)?)
+                        }
+                    }
 
                     // Use
                     "use" => {
                         for path in l {
                             res.extend(token_stream![Ident("use", Span::call_site())]);
-                            res.extend(path_to_token_stream(path, lineno)?);
+                            res.extend(path_to_token_stream(path)?);
                             res.extend(token_stream![Punct(';', Spacing::Alone)]);
                         }
                         break;
                     }
 
+                    // Modules
+                    "mod" => {
+                        res.extend(mod_to_token_stream(l, span)?);
+                        break;
+                    }
+
                     // Control flow
-                    "if" => todo!(),
+                    "if" => {
+                        res.extend(flow::if_to_token_stream(l, span, statement)?);
+                        break;
+                    }
                     "match" => {
-                        res.extend(flow::match_to_token_stream(l, lineno, statement)?);
+                        res.extend(flow::match_to_token_stream(l, span, statement)?);
+                        break;
+                    }
+
+                    // Bindings
+                    "let" => {
+                        res.extend(flow::let_to_token_stream(l, span, statement)?);
+                        break;
+                    }
+
+                    // Blocks
+                    "do" => {
+                        res.extend(block_to_token_stream(l, !statement)?);
                         break;
                     }
 
                     // Loops
-                    "for" => todo!(),
-                    "while" => todo!(),
+                    "for" => {
+                        res.extend(flow::for_to_token_stream(l, span)?);
+                        break;
+                    }
+                    "while" => {
+                        res.extend(flow::while_to_token_stream(l, span)?);
+                        break;
+                    }
                     "loop" => {
-                        res.extend(token_stream![Ident("loop", Span::call_site())]);
-                        res.extend(block_to_token_stream(l, !statement)?);
+                        res.extend(flow::loop_to_token_stream(l, statement)?);
                         break;
                     }
 
                     // break, continue, return
                     "break" | "continue" | "return" => {
                         res.extend(token_stream!(Ident(val, Span::call_site())));
+                        if let Some(label) = l.next_if(|x| val != "return" && is_label(x)) {
+                            res.extend(exp_to_token_stream(label, false, i8::MAX)?)
+                        }
                         if let Some(a) = l.next() {
-                            res.extend(exp_to_token_stream(a, false, i8::MAX))
+                            res.extend(exp_to_token_stream(a, false, i8::MAX)?)
                         }
                         if l.next().is_some() {
                             return Err(Error {
-                                lineno: Some(lineno),
+                                span: Some(span),
                                 kind: RustifyError::TooMuchArguments(val.to_string()),
                             });
                         }
@@ -98,17 +208,40 @@ pub fn list_to_token_stream<'a>(
 
                     // Types
                     "struct" => {
-                        res.extend(types::struct_to_token_stream(l, lineno)?);
-                        break
+                        res.extend(types::struct_to_token_stream(l, span)?);
+                        break;
                     }
                     "enum" => {
-                        res.extend(types::enum_to_token_stream(l, lineno)?);
-                        break
+                        res.extend(types::enum_to_token_stream(l, span)?);
+                        break;
+                    }
+
+                    "trait" => {
+                        res.extend(types::trait_to_token_stream(l, span)?);
+                        break;
+                    }
+                    "type" => {
+                        res.extend(types::type_alias_to_token_stream(
+                            l,
+                            container != Container::Module,
+                            span,
+                        )?);
+                        break;
+                    }
+                    "impl" => {
+                        res.extend(types::impl_to_token_stream(l, span)?);
+                        break;
                     }
 
                     // Functions & closures (lambdas)
                     "fn" => {
-                        res.extend(r#fn::fn_to_token_stream(l, statement, lineno)?);
+                        res.extend(r#fn::fn_to_token_stream(
+                            l,
+                            statement,
+                            precedence,
+                            container == Container::Trait,
+                            span,
+                        )?);
                         break;
                     }
 
@@ -119,14 +252,16 @@ pub fn list_to_token_stream<'a>(
                     }
                 }
             }
+            // Doc comments
+            Sexp::Comment { val, .. } => res.extend(attr::comment_to_token_stream(val)),
             // Function invocation from list
-            Sexp::List(_) => {
+            Sexp::List(..) => {
                 res.extend(call_to_token_stream(exp, l, statement)?);
                 break;
             }
             _ => {
                 return Err(Error {
-                    lineno: None,
+                    span: Some(exp.span()),
                     kind: RustifyError::UnexpectedFunctionName(rustify(exp)?.to_string()),
                 })
             }
@@ -134,3 +269,42 @@ pub fn list_to_token_stream<'a>(
     }
     Ok(res)
 }
+
+/// Checks if `exp` starts a (possibly qualified) function definition (e.g. `unsafe fn`).
+fn starts_fn(exp: Option<&&Sexp>) -> bool {
+    matches!(exp, Some(Sexp::Atom { val, .. }) if matches!(val.as_str(), "fn" | "unsafe" | "async" | "extern"))
+}
+
+/// Checks if `exp` starts an item qualified by `unsafe` (e.g. `unsafe impl`), instead of a block.
+fn starts_unsafe_item(exp: Option<&&Sexp>) -> bool {
+    matches!(exp, Some(Sexp::Atom { val, .. }) if matches!(val.as_str(), "fn" | "extern" | "impl" | "trait"))
+}
+
+/// Checks if the list form `l` can contain comments, attached to its statements, items, fields,
+/// variants or arms (e.g. `(fn f () ;;; doc ...)`).
+///
+/// Other forms drop them, so that they do not take the place of the arguments (e.g. `(if c ;;; a
+/// x y)`).
+fn keeps_comments(l: &[&Sexp]) -> bool {
+    let mut after_pub = false;
+    for exp in l {
+        match exp {
+            Sexp::Atom { val, .. } => match val.as_str() {
+                "fn" | "do" | "loop" | "while" | "for" | "unsafe" | "match" | "struct" | "enum"
+                | "trait" | "impl" | "mod" => return true,
+                // Qualifiers and attributes
+                "pub" | "const" | "async" | "extern" | "#" | "#!" => {}
+                // ABI
+                abi if abi.starts_with('"') => {}
+                _ => return false,
+            },
+            // Attribute content or doc comment
+            Sexp::Array(..) | Sexp::Comment { .. } => {}
+            // Visibility restriction
+            Sexp::List(..) if after_pub => {}
+            _ => return false,
+        }
+        after_pub = matches!(exp, Sexp::Atom { val, .. } if val == "pub");
+    }
+    false
+}
@@ -36,6 +36,8 @@ macro_rules! interspere_token_stream {
         #[allow(unstable_name_collisions)]
         $list
             .into_iter()
+            // Comments cannot be attached to separated elements (e.g. arguments)
+            .filter(|x| !matches!(x, Sexp::Comment { .. }))
             .map($mapper)
             .intersperse_with(|| Ok($separator.punct_as_token_stream()))
             .collect::<Result>()
@@ -11,9 +11,22 @@ impl PunctAsTokenStream for char {
 }
 
 impl PunctAsTokenStream for &str {
-    #[inline]
+    /// Writes joint punctuation, followed by a keyword, if any (e.g. `&mut`, `as`).
     fn punct_as_token_stream(&self) -> TokenStream {
-        TokenStream::from_str(self).unwrap()
+        let keyword = self.trim_start_matches(|c: char| c.is_ascii_punctuation());
+        let punct = &self[..self.len() - keyword.len()];
+        let mut res = TokenStream::from_iter(punct.char_indices().map(|(i, c)| {
+            let spacing = if i + 1 < punct.len() {
+                Spacing::Joint
+            } else {
+                Spacing::Alone
+            };
+            token_tree!(Punct(c, spacing))
+        }));
+        if !keyword.is_empty() {
+            res.extend(token_stream![Ident(keyword, Span::call_site())]);
+        }
+        res
     }
 }
 
@@ -23,7 +36,8 @@ pub fn precedence(op: &str, unary: bool) -> i8 {
         "." | "::" => 0,
         // Method calls => 1,
         // Field expressions => 2,
-        // Function calls, array indexing => 3,
+        // Function calls, array indexing
+        "()" => 3,
         "?" => 4,
         "-" | "!" | "&" | "&mut" | "*" | "*mut" if unary => 5,
         "as" => 6,
@@ -38,6 +52,8 @@ pub fn precedence(op: &str, unary: bool) -> i8 {
         "||" => 15,
         ".." | "..=" => 16,
         "=" | "+=" | "-=" | "*=" | "/=" | "%=" | "&=" | "|=" | "^=" | "<<=" | ">>=" => 17,
+        // Closures (they can be the right operand of an assignment without parentheses)
+        "fn" => 17,
         _ => i8::MAX / 2,
     }
 }
@@ -62,17 +78,36 @@ fn associativity(op: &str, unary: bool) -> i8 {
 pub fn op_to_token_stream<'a>(
     op: &str,
     operands: impl Iterator<Item = &'a Sexp>,
-    lineno: usize,
+    span: crate::Span,
     statement: bool,
     parent_precedence: i8,
 ) -> Result {
     let mut operands = operands.multipeek();
 
-    if operands.peek().is_none() {
-        return Err(Error {
-            lineno: Some(lineno),
-            kind: RustifyError::MissingOperand(op.to_string()),
-        });
+    let lifetime = match operands.peek() {
+        Some(x) => is_label(x),
+        None => {
+            return Err(Error {
+                span: Some(span),
+                kind: RustifyError::MissingOperand(op.to_string()),
+            })
+        }
+    };
+
+    // Reference types with a lifetime (e.g. `(& 'a T)`, `(&mut 'a T)`)
+    if lifetime && matches!(op, "&" | "&mut") {
+        let mut operands = operands.peekable();
+        let mut res = types::reference_to_token_stream(op, &mut operands, span)?;
+        if operands.next().is_some() {
+            return Err(Error {
+                span: Some(span),
+                kind: RustifyError::TooMuchArguments(op.to_string()),
+            });
+        }
+        if statement {
+            res.extend(token_stream![Punct(';', Spacing::Alone)])
+        }
+        return Ok(res);
     }
 
     let unary = operands.peek().is_none();
@@ -82,7 +117,10 @@ pub fn op_to_token_stream<'a>(
         // Prefix operator
         let mut res = op.punct_as_token_stream();
         res.extend(exp_to_token_stream(
-            operands.next().unwrap(),
+            operands.next().ok_or(Error {
+                span: Some(span),
+                kind: RustifyError::MissingOperand(op.to_string()),
+            })?,
             false,
             precedence,
         )?);
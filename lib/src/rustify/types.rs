@@ -1,35 +1,487 @@
 use super::*;
+use core::iter::Peekable;
 
-pub fn struct_to_token_stream<'a>(mut l: impl Iterator<Item = &'a Sexp>, lineno: usize) -> Result {
-    let mut res = token_stream![Ident("struct", Span::call_site())];
+pub fn struct_to_token_stream<'a>(l: impl Iterator<Item = &'a Sexp>, span: crate::Span) -> Result {
+    let mut l = l.peekable();
+    let mut res = header_to_token_stream("struct", &mut l, span)?;
+    let where_clause = where_clause_to_token_stream(&mut l)?;
+    if l.peek().is_none() {
+        // Unit struct
+        res.extend(where_clause);
+        res.extend(token_stream![Punct(';', Spacing::Alone)]);
+        return Ok(res);
+    }
+    let fields = fields_to_token_stream(l, span)?;
+    if fields.delimiter() == Delimiter::Brace {
+        res.extend(where_clause);
+        res.extend(TokenStream::from(TokenTree::Group(fields)));
+    } else {
+        res.extend(TokenStream::from(TokenTree::Group(fields)));
+        res.extend(where_clause);
+        res.extend(token_stream![Punct(';', Spacing::Alone)]);
+    }
+    Ok(res)
+}
+
+pub fn enum_to_token_stream<'a>(l: impl Iterator<Item = &'a Sexp>, span: crate::Span) -> Result {
+    let mut l = l.peekable();
+    let mut res = header_to_token_stream("enum", &mut l, span)?;
+    res.extend(where_clause_to_token_stream(&mut l)?);
+    let mut variants = token_stream![];
+    while l.peek().is_some() {
+        variants.extend(attr::attrs_to_token_stream(&mut l)?);
+        variants.extend(variant_to_token_stream(l.next().ok_or(Error {
+            span: Some(span),
+            kind: RustifyError::MissingArguments("variant".into()),
+        })?)?);
+        if l.peek().is_some() {
+            variants.extend(token_stream![Punct(',', Spacing::Alone)]);
+        }
+    }
+    res.extend(token_stream![Group(Delimiter::Brace, variants)]);
+    Ok(res)
+}
+
+/// Writes an enum variant (e.g. `A`, `(= A 4)`, `(B u8)`, `(C :a u8)`).
+fn variant_to_token_stream(variant: &Sexp) -> Result {
+    match variant {
+        Sexp::List(v, _) => match v.split_first() {
+            Some((name @ Sexp::Atom { val, span }, fields)) if val != "=" => {
+                let mut res = exp_to_token_stream(name, false, i8::MAX)?;
+                res.extend(TokenStream::from(TokenTree::Group(fields_to_token_stream(
+                    fields.iter(),
+                    *span,
+                )?)));
+                Ok(res)
+            }
+            _ => exp_to_token_stream(variant, false, i8::MAX),
+        },
+        _ => exp_to_token_stream(variant, false, i8::MAX),
+    }
+}
+
+pub fn impl_to_token_stream<'a>(l: impl Iterator<Item = &'a Sexp>, span: crate::Span) -> Result {
+    let mut l = l.peekable();
+    let mut res = token_stream![Ident("impl", Span::call_site())];
+    if let Some(Sexp::Generics(g, _)) = l.next_if(|x| matches!(x, Sexp::Generics(..))) {
+        res.extend(generics_to_token_stream(g)?);
+    }
+    res.extend(type_to_token_stream(
+        l.next().ok_or(Error {
+            span: Some(span),
+            kind: RustifyError::MissingArguments("impl".into()),
+        })?,
+        &mut l,
+    )?);
+    if l.next_if(|x| matches!(x, Sexp::Atom { val, .. } if val == "for"))
+        .is_some()
+    {
+        res.extend(token_stream![Ident("for", Span::call_site())]);
+        res.extend(type_to_token_stream(
+            l.next().ok_or(Error {
+                span: Some(span),
+                kind: RustifyError::MissingArguments("impl".into()),
+            })?,
+            &mut l,
+        )?);
+    }
+    res.extend(where_clause_to_token_stream(&mut l)?);
+    res.extend(items_to_token_stream(l, Container::Impl)?);
+    Ok(res)
+}
+
+pub fn trait_to_token_stream<'a>(l: impl Iterator<Item = &'a Sexp>, span: crate::Span) -> Result {
+    let mut l = l.peekable();
+    let mut res = header_to_token_stream("trait", &mut l, span)?;
+    if let Some(supertraits) = l.peek().and_then(|x| form_args(x, ":")) {
+        l.next();
+        res.extend(bounds_to_token_stream(supertraits.iter().peekable())?);
+    }
+    res.extend(where_clause_to_token_stream(&mut l)?);
+    res.extend(items_to_token_stream(l, Container::Trait)?);
+    Ok(res)
+}
+
+/// Writes a type alias, or an associated type if `associated` (e.g. `(type Item)`,
+/// `(type (:Item Clone))`, `(type Item u8)`, `(type Pair<T> (where (:T Clone)) (, T T))`).
+pub fn type_alias_to_token_stream<'a>(
+    l: impl Iterator<Item = &'a Sexp>,
+    associated: bool,
+    span: crate::Span,
+) -> Result {
+    let mut l = l.peekable();
+    let mut res = token_stream![Ident("type", Span::call_site())];
     match l.next() {
-        Some(Sexp::Atom { val, .. }) => {
-            res.extend(token_stream![
-                Ident(val, Span::call_site()),
-                Punct(';', Spacing::Alone)
-            ]);
+        Some(Sexp::Atom { val, span }) => {
+            res.extend(TokenStream::from_str(val).map_err(|e| Error {
+                span: Some(*span),
+                kind: RustifyError::AtomParseError(val.to_string(), e),
+            })?);
+            if let Some(Sexp::Generics(g, _)) = l.next_if(|x| matches!(x, Sexp::Generics(..))) {
+                res.extend(generics_to_token_stream(g)?);
+            }
+        }
+        Some(bound @ Sexp::List(..)) => {
+            res.extend(params_to_token_stream(core::slice::from_ref(bound))?)
+        }
+        _ => {
+            return Err(Error {
+                span: Some(span),
+                kind: RustifyError::MissingArguments("type".into()),
+            })
+        }
+    }
+    // The where clause of associated types follows the type (e.g. `type A<T> = B<T> where T: C;`),
+    // while it precedes `=` in type aliases
+    let where_clause = where_clause_to_token_stream(&mut l)?;
+    if !associated {
+        res.extend(where_clause.clone());
+    }
+    if let Some(ty) = l.next() {
+        res.extend(token_stream![Punct('=', Spacing::Alone)]);
+        res.extend(type_to_token_stream(ty, &mut l)?);
+        if l.next().is_some() {
+            return Err(Error {
+                span: Some(span),
+                kind: RustifyError::TooMuchArguments("type".into()),
+            });
+        }
+    }
+    if associated {
+        res.extend(where_clause);
+    }
+    res.extend(token_stream![Punct(';', Spacing::Alone)]);
+    Ok(res)
+}
+
+/// Writes a constant or a static (e.g. `(const (:N usize) 5)`, `(static mut (:COUNT u32) 0)`),
+/// or its declaration if the value is missing.
+pub fn const_to_token_stream<'a>(
+    keyword: &str,
+    l: impl Iterator<Item = &'a Sexp>,
+    span: crate::Span,
+) -> Result {
+    let mut l = l.peekable();
+    let mut res = token_stream![Ident(keyword, Span::call_site())];
+    if keyword == "static"
+        && l.next_if(|x| matches!(x, Sexp::Atom { val, .. } if val == "mut"))
+            .is_some()
+    {
+        res.extend(token_stream![Ident("mut", Span::call_site())]);
+    }
+    res.extend(binding_to_token_stream(l.next().ok_or(Error {
+        span: Some(span),
+        kind: RustifyError::MissingArguments(keyword.into()),
+    })?)?);
+    if let Some(val) = l.next() {
+        res.extend(token_stream![Punct('=', Spacing::Alone)]);
+        res.extend(exp_to_token_stream(val, false, i8::MAX)?);
+    }
+    if l.next().is_some() {
+        return Err(Error {
+            span: Some(span),
+            kind: RustifyError::TooMuchArguments(keyword.into()),
+        });
+    }
+    res.extend(token_stream![Punct(';', Spacing::Alone)]);
+    Ok(res)
+}
+
+/// Writes the keyword and the name of a type definition, followed by its generics, if any.
+fn header_to_token_stream<'a>(
+    keyword: &str,
+    l: &mut Peekable<impl Iterator<Item = &'a Sexp>>,
+    span: crate::Span,
+) -> Result {
+    let mut res = token_stream![Ident(keyword, Span::call_site())];
+    match l.next() {
+        Some(Sexp::Atom { val, span }) => {
+            res.extend(ident_to_token_stream(val, *span)?);
+            if let Some(Sexp::Generics(g, _)) = l.next_if(|x| matches!(x, Sexp::Generics(..))) {
+                res.extend(generics_to_token_stream(g)?);
+            }
             Ok(res)
         }
         _ => Err(Error {
-            lineno: Some(lineno),
-            kind: RustifyError::MissingArguments("struct definition".into()),
+            span: Some(span),
+            kind: RustifyError::MissingArguments(format!("{} definition", keyword)),
         }),
     }
 }
 
-pub fn enum_to_token_stream<'a>(mut l: impl Iterator<Item = &'a Sexp>, lineno: usize) -> Result {
-    let mut res = token_stream![Ident("enum", Span::call_site())];
-    match l.next() {
-        Some(Sexp::Atom { val, .. }) => {
-            res.extend(token_stream![
-                Ident(val, Span::call_site()),
-                Group(Delimiter::Brace, interspere_token_stream!(l)?)
-            ]);
+/// Writes the `(where ...)` clause, if any.
+pub fn where_clause_to_token_stream<'a>(
+    l: &mut Peekable<impl Iterator<Item = &'a Sexp>>,
+) -> Result {
+    match l.peek().and_then(|x| form_args(x, "where")) {
+        Some(w) => {
+            l.next();
+            where_to_token_stream(w)
+        }
+        None => Ok(token_stream![]),
+    }
+}
+
+/// Writes the fields of a struct, surrounded by braces if named (e.g. `:a u8 (pub :b String)`),
+/// or by parentheses if unnamed (e.g. `u8 (pub String)`).
+pub fn fields_to_token_stream<'a>(
+    l: impl Iterator<Item = &'a Sexp>,
+    span: crate::Span,
+) -> core::result::Result<Group, Error<RustifyError>> {
+    let mut l = l.peekable();
+    let mut named = None;
+    let mut res = token_stream![];
+    while l.peek().is_some() {
+        res.extend(attr::attrs_to_token_stream(&mut l)?);
+        let field = l.next().ok_or(Error {
+            span: Some(span),
+            kind: RustifyError::MissingArguments("field".into()),
+        })?;
+        res.extend(match field {
+            Sexp::List(f, _) if is_pub(f) => {
+                let mut f = f[1..].iter().peekable();
+                let mut res = vis_to_token_stream(&mut f)?;
+                let named = *named.get_or_insert_with(|| is_field_name(f.peek().copied()));
+                res.extend(field_to_token_stream(
+                    f.next().ok_or(Error {
+                        span: Some(span),
+                        kind: RustifyError::MissingArguments("pub".into()),
+                    })?,
+                    &mut f,
+                    named,
+                    span,
+                )?);
+                if f.next().is_some() {
+                    return Err(Error {
+                        span: Some(span),
+                        kind: RustifyError::TooMuchArguments("field".into()),
+                    });
+                }
+                res
+            }
+            _ => {
+                let named = *named.get_or_insert_with(|| is_field_name(Some(field)));
+                field_to_token_stream(field, &mut l, named, span)?
+            }
+        });
+        if l.peek().is_some() {
+            res.extend(token_stream![Punct(',', Spacing::Alone)]);
+        }
+    }
+    Ok(Group::new(
+        if named == Some(true) {
+            Delimiter::Brace
+        } else {
+            Delimiter::Parenthesis
+        },
+        res,
+    ))
+}
+
+/// Writes a single field (e.g. `:a u8` if `named`, `u8` otherwise).
+fn field_to_token_stream<'a>(
+    field: &Sexp,
+    l: &mut Peekable<impl Iterator<Item = &'a Sexp>>,
+    named: bool,
+    span: crate::Span,
+) -> Result {
+    if !named {
+        return type_to_token_stream(field, l);
+    }
+    match field {
+        Sexp::Atom { val, span } if is_field_name(Some(field)) => {
+            let mut res = ident_to_token_stream(&val[1..], *span)?;
+            res.extend(token_stream![Punct(':', Spacing::Alone)]);
+            res.extend(type_to_token_stream(
+                l.next().ok_or(Error {
+                    span: Some(*span),
+                    kind: RustifyError::MissingArguments(format!("field {}", &val[1..])),
+                })?,
+                l,
+            )?);
             Ok(res)
         }
         _ => Err(Error {
-            lineno: Some(lineno),
-            kind: RustifyError::MissingArguments("enum definition".into()),
+            span: Some(span),
+            kind: RustifyError::MissingArguments("field name".into()),
         }),
     }
 }
+
+/// Checks if `f` is a field name (e.g. `:a`).
+fn is_field_name(f: Option<&Sexp>) -> bool {
+    matches!(f, Some(Sexp::Atom { val, .. }) if val.starts_with(':') && val != "::" && val != ":")
+}
+
+/// Checks if the list `l` starts with `pub`.
+fn is_pub(l: &[Sexp]) -> bool {
+    matches!(l.first(), Some(Sexp::Atom { val, .. }) if val == "pub")
+}
+
+/// Writes the type `ty`, gluing it to the following generics (e.g. `Vec <u8>`), if any.
+///
+/// Reference and pointer prefixes (e.g. `& [u8]`, `& 'a mut T`, `*const u8`) and higher-ranked
+/// lifetimes (e.g. `for <'a> Visitor <'a>`) are glued to the following type.
+pub fn type_to_token_stream<'a>(
+    ty: &Sexp,
+    l: &mut Peekable<impl Iterator<Item = &'a Sexp>>,
+) -> Result {
+    if let Sexp::Atom { val, span } = ty {
+        match val.as_str() {
+            "&" | "&mut" => return reference_to_token_stream(val, l, *span),
+            "*const" | "*mut" => {
+                let mut res = exp_to_token_stream(ty, false, i8::MAX)?;
+                res.extend(type_to_token_stream(
+                    l.next().ok_or(Error {
+                        span: Some(*span),
+                        kind: RustifyError::MissingArguments(val.to_string()),
+                    })?,
+                    l,
+                )?);
+                return Ok(res);
+            }
+            "for" => {
+                if let Some(Sexp::Generics(g, _)) = l.next_if(|x| matches!(x, Sexp::Generics(..))) {
+                    let mut res = token_stream![Ident("for", Span::call_site())];
+                    res.extend(generics_to_token_stream(g)?);
+                    res.extend(type_to_token_stream(
+                        l.next().ok_or(Error {
+                            span: Some(*span),
+                            kind: RustifyError::MissingArguments("for".into()),
+                        })?,
+                        l,
+                    )?);
+                    return Ok(res);
+                }
+            }
+            _ => (),
+        }
+    }
+    let mut res = exp_to_token_stream(ty, false, i8::MAX)?;
+    if let Some(Sexp::Generics(g, _)) = l.next_if(|x| matches!(x, Sexp::Generics(..))) {
+        res.extend(generics_to_token_stream(g)?);
+    }
+    Ok(res)
+}
+
+/// Writes a reference type (e.g. `&'a mut T`) from `op` (`&` or `&mut`), followed by an optional
+/// lifetime, an optional `mut` and the referenced type.
+pub fn reference_to_token_stream<'a>(
+    op: &str,
+    l: &mut Peekable<impl Iterator<Item = &'a Sexp>>,
+    span: crate::Span,
+) -> Result {
+    let mut res = token_stream![Punct('&', Spacing::Alone)];
+    if let Some(lifetime) = l.next_if(|x| is_label(x)) {
+        res.extend(exp_to_token_stream(lifetime, false, i8::MAX)?);
+    }
+    if op == "&mut"
+        || l.next_if(|x| matches!(x, Sexp::Atom { val, .. } if val == "mut"))
+            .is_some()
+    {
+        res.extend(token_stream![Ident("mut", Span::call_site())]);
+    }
+    res.extend(type_to_token_stream(
+        l.next().ok_or(Error {
+            span: Some(span),
+            kind: RustifyError::MissingArguments(op.to_string()),
+        })?,
+        l,
+    )?);
+    Ok(res)
+}
+
+/// Writes the type made by all the expressions in `l`.
+pub fn types_to_token_stream(l: &[Sexp], span: crate::Span) -> Result {
+    let mut l = l.iter().peekable();
+    let res = type_to_token_stream(
+        l.next().ok_or(Error {
+            span: Some(span),
+            kind: RustifyError::MissingArguments("type".into()),
+        })?,
+        &mut l,
+    )?;
+    if l.next().is_some() {
+        return Err(Error {
+            span: Some(span),
+            kind: RustifyError::TooMuchArguments("type".into()),
+        });
+    }
+    Ok(res)
+}
+
+/// Writes a list of generics (e.g. `<T (:U Clone Debug)>`).
+pub fn generics_to_token_stream(l: &[Sexp]) -> Result {
+    let mut res = token_stream![Punct('<', Spacing::Joint)];
+    res.extend(params_to_token_stream(l)?);
+    res.extend(token_stream![Punct('>', Spacing::Alone)]);
+    Ok(res)
+}
+
+/// Writes a `where` clause (e.g. `(where (:T Clone) (:U Debug))`).
+fn where_to_token_stream(l: &[Sexp]) -> Result {
+    let mut res = token_stream![Ident("where", Span::call_site())];
+    res.extend(params_to_token_stream(l)?);
+    Ok(res)
+}
+
+/// Writes comma separated types and bounds.
+fn params_to_token_stream(l: &[Sexp]) -> Result {
+    // Comments cannot be attached to generic parameters
+    let mut l = l
+        .iter()
+        .filter(|x| !matches!(x, Sexp::Comment { .. }))
+        .peekable();
+    let mut res = token_stream![];
+    while let Some(exp) = l.next() {
+        res.extend(match exp {
+            Sexp::List(b, _) => match b.split_first() {
+                Some((Sexp::Atom { val, span }, bounds)) if val == ":" => {
+                    let mut bounds = bounds.iter().peekable();
+                    let mut res = type_to_token_stream(
+                        bounds.next().ok_or(Error {
+                            span: Some(*span),
+                            kind: RustifyError::MissingArguments("bound".into()),
+                        })?,
+                        &mut bounds,
+                    )?;
+                    res.extend(bounds_to_token_stream(bounds)?);
+                    res
+                }
+                Some((Sexp::Atom { val, span }, bounds)) if val.starts_with(':') && val != "::" => {
+                    let mut res = match &val[1..] {
+                        // Lifetime (e.g. `(:'b 'a)`)
+                        lifetime if lifetime.starts_with('\'') => TokenStream::from_str(lifetime)
+                            .map_err(|e| Error {
+                            span: Some(*span),
+                            kind: RustifyError::AtomParseError(val.to_string(), e),
+                        })?,
+                        name => ident_to_token_stream(name, *span)?,
+                    };
+                    res.extend(bounds_to_token_stream(bounds.iter().peekable())?);
+                    res
+                }
+                _ => type_to_token_stream(exp, &mut l)?,
+            },
+            _ => type_to_token_stream(exp, &mut l)?,
+        });
+        if l.peek().is_some() {
+            res.extend(token_stream![Punct(',', Spacing::Alone)]);
+        }
+    }
+    Ok(res)
+}
+
+/// Writes the bounds of a generic (e.g. `: Clone + Debug`), if any.
+fn bounds_to_token_stream<'a>(mut l: Peekable<impl Iterator<Item = &'a Sexp>>) -> Result {
+    let mut res = token_stream![];
+    if let Some(bound) = l.next() {
+        res.extend(token_stream![Punct(':', Spacing::Alone)]);
+        res.extend(type_to_token_stream(bound, &mut l)?);
+        while let Some(bound) = l.next() {
+            res.extend(token_stream![Punct('+', Spacing::Alone)]);
+            res.extend(type_to_token_stream(bound, &mut l)?);
+        }
+    }
+    Ok(res)
+}
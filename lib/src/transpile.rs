@@ -0,0 +1,160 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use proc_macro2::TokenStream;
+use std::{fs, path::Path};
+
+use crate::{
+    parse, parser::Sexp, rustify::doc_comment, rustify_validated, validate::verbatim, Diagnostic,
+    Error, ErrorKind, ParseError,
+};
+
+/// Transpiles srs source code into formatted Rust code.
+///
+/// All the erroneous forms are reported, in the order they appear in the input.
+///
+/// # Example
+///
+/// ```
+/// use srs::transpile;
+///
+/// assert_eq!(
+///     transpile("(fn main () (println! \"Hello World!\"))").unwrap(),
+///     "fn main() {\n    println!(\"Hello World!\");\n}\n"
+/// );
+/// assert_eq!(transpile("(fn f () (+))\n(f x").unwrap_err().len(), 2);
+/// ```
+#[inline]
+pub fn transpile(input: &str) -> Result<String, Vec<Diagnostic>> {
+    transpile_parsed(parse(input))
+}
+
+/// Reads and transpiles the srs file at `path`, like [`transpile`].
+pub fn transpile_file(path: impl AsRef<Path>) -> Result<String, Vec<Diagnostic>> {
+    let input = fs::read_to_string(path).map_err(|e| {
+        vec![Diagnostic {
+            span: None,
+            kind: ErrorKind::Io(e.to_string()),
+        }]
+    })?;
+    transpile(&input)
+}
+
+/// Transpiles already parsed s-expressions (e.g. from [`parse_lines`](crate::parse_lines)) into
+/// formatted Rust code, like [`transpile`].
+///
+/// The top-level plain comments kept by [`parse_with_comments`](crate::parse_with_comments) are
+/// written as line comments (e.g. `;; note` becomes `// note`), but cannot separate a doc comment
+/// from the form it documents. The ones inside a form are dropped.
+///
+/// # Example
+///
+/// ```
+/// use srs::{parse_with_comments, transpile_parsed};
+///
+/// assert_eq!(
+///     transpile_parsed(parse_with_comments(";; Entry point\n(fn main ())")).unwrap(),
+///     "// Entry point\nfn main() {}\n"
+/// );
+/// ```
+pub fn transpile_parsed(
+    exps: impl IntoIterator<Item = Result<Sexp, Error<ParseError>>>,
+) -> Result<String, Vec<Diagnostic>> {
+    let mut diagnostics = Vec::new();
+    let mut res = String::new();
+    let mut token_stream = TokenStream::new();
+    // An outer doc comment documents the following form, so they are formatted together
+    let mut documenting = false;
+
+    for exp in exps {
+        match exp.map_err(Diagnostic::from) {
+            Ok(Sexp::Comment { val, span }) if doc_comment(&val).is_none() => {
+                if documenting {
+                    // It could only be written before the doc comment, out of order
+                    diagnostics.push(Diagnostic {
+                        span: Some(span),
+                        kind: ErrorKind::Syntax(
+                            "comment between a doc comment and the documented form".to_string(),
+                        ),
+                    });
+                } else if diagnostics.is_empty() {
+                    res += &unparse(core::mem::take(&mut token_stream)).map_err(|e| vec![e])?;
+                    res += &line_comment(&val);
+                    res.push('\n');
+                }
+            }
+            Ok(exp) => match rustify_validated(&exp) {
+                Ok(form) => {
+                    documenting = matches!(&exp, Sexp::Comment { val, .. }
+                        if doc_comment(val).is_some_and(|(inner, _)| !inner));
+                    token_stream.extend(form);
+                }
+                Err(e) => diagnostics.push(e),
+            },
+            Err(e) => diagnostics.push(e),
+        }
+    }
+
+    if !diagnostics.is_empty() {
+        return Err(diagnostics);
+    }
+    res += &unparse(token_stream).map_err(|e| vec![e])?;
+    Ok(res)
+}
+
+/// Formats the Rust code of whole forms.
+fn unparse(token_stream: TokenStream) -> Result<String, Diagnostic> {
+    if token_stream.is_empty() {
+        return Ok(String::new());
+    }
+    // Checked again, since the forms are validated one by one (e.g. a trailing outer doc comment)
+    let file = syn::parse2(token_stream).map_err(|e| Diagnostic {
+        span: None,
+        kind: ErrorKind::Syntax(e.to_string()),
+    })?;
+    // The printer panics on the code it cannot format
+    if let Some(tokens) = verbatim(&file) {
+        return Err(Diagnostic {
+            span: None,
+            kind: ErrorKind::Syntax(format!("unsupported syntax `{}`", tokens)),
+        });
+    }
+    Ok(prettyplease::unparse(&file))
+}
+
+/// Writes a plain comment as a Rust line comment, with a `/` for each leading `;` (e.g. `;; note`
+/// becomes `// note`, `;;;; note` becomes `//// note`).
+fn line_comment(comment: &str) -> String {
+    let text = comment.trim_start_matches(';');
+    let slashes = (comment.len() - text.len()).max(2);
+    "/".repeat(slashes) + text
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{transpile, ErrorKind};
+
+    #[test]
+    fn empty_fn() {
+        assert_eq!(transpile("(fn main ())").unwrap(), "fn main() {}\n");
+    }
+
+    #[test]
+    fn bodiless_items() {
+        let res = transpile(
+            "(const (:X u8))\n(static (:Y u8))\n(type Item)\n(impl X (fn f ()))\n\
+             (impl X (const (:A u8)))\n(impl X (type B))\n(trait T (fn f ()) (type C))",
+        );
+        let lines = res
+            .unwrap_err()
+            .into_iter()
+            .map(|e| {
+                assert!(matches!(e.kind, ErrorKind::Syntax(_)));
+                e.span.unwrap().start.line
+            })
+            .collect::<alloc::vec::Vec<_>>();
+        assert_eq!(lines, [1, 2, 3, 5, 6]);
+    }
+}
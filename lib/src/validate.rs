@@ -0,0 +1,87 @@
+use alloc::{format, string::ToString};
+use proc_macro2::TokenStream;
+use syn::visit::{self, Visit};
+
+use crate::{parser::Sexp, rustify, Diagnostic, ErrorKind};
+
+/// Generates Rust code from a top-level s-expression like [`rustify()`], checking with `syn` that
+/// the result is valid Rust (i.e. items and inner attributes).
+///
+/// Code that `syn` only keeps as raw tokens is rejected too, even nested (e.g. `const X: u8;`
+/// outside of a trait).
+///
+/// Syntax errors of the generated code are reported at the span of `exp`. Comments are not
+/// checked, since outer doc comments document the following form.
+///
+/// # Example
+///
+/// ```
+/// use srs::{parse, rustify_validated, ErrorKind};
+///
+/// let mut exps = parse("(fn main () (f x))\n(f x)\n(trait T (const (:A u8)))\n(impl T (const (:A u8)))")
+///     .map(Result::unwrap);
+/// assert!(rustify_validated(&exps.next().unwrap()).is_ok());
+///
+/// let err = rustify_validated(&exps.next().unwrap()).unwrap_err();
+/// assert!(matches!(err.kind, ErrorKind::Syntax(_)));
+/// assert_eq!(err.span.unwrap().start.line, 2);
+///
+/// // A constant needs a value, unless it is a trait item
+/// assert!(rustify_validated(&exps.next().unwrap()).is_ok());
+/// let err = rustify_validated(&exps.next().unwrap()).unwrap_err();
+/// assert!(matches!(err.kind, ErrorKind::Syntax(_)));
+/// assert_eq!(err.span.unwrap().start.line, 4);
+/// ```
+pub fn rustify_validated(exp: &Sexp) -> Result<TokenStream, Diagnostic> {
+    let res = rustify(exp)?;
+    if let Sexp::Comment { .. } = exp {
+        return Ok(res);
+    }
+    let error = match syn::parse2::<syn::File>(res.clone()) {
+        Ok(file) => match verbatim(&file) {
+            Some(tokens) => format!("unsupported syntax `{}`", tokens),
+            None => return Ok(res),
+        },
+        Err(e) => e.to_string(),
+    };
+    Err(Diagnostic {
+        span: Some(exp.span()),
+        kind: ErrorKind::Syntax(error),
+    })
+}
+
+/// Returns the first code of `file` that `syn` keeps as raw tokens, which cannot be formatted.
+pub(crate) fn verbatim(file: &syn::File) -> Option<TokenStream> {
+    let mut verbatim = Verbatim(None);
+    verbatim.visit_file(file);
+    verbatim.0
+}
+
+/// Finds the first code that `syn` keeps as raw tokens.
+struct Verbatim(Option<TokenStream>);
+
+macro_rules! visit_verbatim {
+    ($($visit: ident($ty: ident);)*) => {
+        $(
+            fn $visit(&mut self, node: &'ast syn::$ty) {
+                match node {
+                    syn::$ty::Verbatim(tokens) => {
+                        self.0.get_or_insert_with(|| tokens.clone());
+                    }
+                    _ => visit::$visit(self, node),
+                }
+            }
+        )*
+    };
+}
+
+impl<'ast> Visit<'ast> for Verbatim {
+    visit_verbatim! {
+        visit_item(Item);
+        visit_impl_item(ImplItem);
+        visit_trait_item(TraitItem);
+        visit_foreign_item(ForeignItem);
+        visit_expr(Expr);
+        visit_type_param_bound(TypeParamBound);
+    }
+}
@@ -0,0 +1,92 @@
+mod common;
+
+test_transpile! {
+    item_attributes: r##"
+        (#![no_std])
+        (#![allow dead_code unused_imports])
+
+        (#[derive Debug Clone] #[repr C] pub struct Point
+          :x i32
+          :y i32)
+
+        (#[inline] fn f ())
+
+        (#[cfg (not (= feature "std"))] use (:: alloc vec Vec))
+
+        (#[cfg test]
+         fn only_in_tests ())
+    "## => {
+        #![no_std]
+        #![allow(dead_code, unused_imports)]
+
+        #[derive(Debug, Clone)]
+        #[repr(C)]
+        pub struct Point {
+            x: i32,
+            y: i32
+        }
+
+        #[inline]
+        fn f() {}
+
+        #[cfg(not(feature = "std"))]
+        use alloc::vec::Vec;
+
+        #[cfg(test)]
+        fn only_in_tests() {}
+    }
+
+    field_and_variant_attributes: r##"
+        (struct Config
+          #[serde (= rename "Name")] :name String
+          #[serde skip] (pub :cache Vec<u8>))
+
+        (struct Wrapper #[allow unused] u8)
+
+        (#[derive Default] enum State
+          #[default] Idle
+          #[allow dead_code] (Running u32)
+          (Done #[doc = "exit code"] :code i32))
+    "## => {
+        struct Config {
+            #[serde(rename = "Name")]
+            name: String,
+            #[serde(skip)]
+            pub cache: Vec<u8>
+        }
+
+        struct Wrapper(#[allow(unused)] u8);
+
+        #[derive(Default)]
+        enum State {
+            #[default]
+            Idle,
+            #[allow(dead_code)]
+            Running(u32),
+            Done {
+                #[doc = "exit code"]
+                code: i32
+            }
+        }
+    }
+
+    statement_and_arm_attributes: r##"
+        (#[test]
+         fn check ()
+          (#[allow unused_variables] let x 5)
+          (match x
+            (#[cfg unix] 5 (f))
+            (_ (g))))
+    "## => {
+        #[test]
+        fn check() {
+            #[allow(unused_variables)]
+            let x = 5;
+            match x {
+                #[cfg(unix)]
+                5 => f(),
+                _ => g(),
+            };
+        }
+    }
+}
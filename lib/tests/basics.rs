@@ -9,7 +9,6 @@ test_transpile! {
         }
     }
 
-    // TODO
     guess_game: r##"
         (use (:: rand Rng)
              (:: std ((:: cmp Ordering)
@@ -18,25 +17,22 @@ test_transpile! {
         (fn main ()
          (println! "Guess the number!")
 
-         ;; let secret_number =
-         (. ((:: rand thread_rng)) (gen_range (.. 1 101)))
+         (let secret_number (. ((:: rand thread_rng)) (gen_range (.. 1 101))))
          ;; or
-         ((. ((:: rand thread_rng)) gen_range) (.. 1 101))
+         (let secret_number ((. ((:: rand thread_rng)) gen_range) (.. 1 101)))
 
          (loop
           (println! "Please input your guess.")
 
-          ;;     let mut guess =
-          ((:: String new))
+          (let mut guess ((:: String new)))
 
           (. ((:: io stdin)) (read_line (&mut guess)) (expect "Failed to read line"))
           ;; or
           ((.((. ((:: io stdin)) read_line) (&mut guess)) expect) "Failed to read line")
 
-          ;;     let guess: u32 =
-          (match (. guess (trim) (parse))
-            ((Ok num) num)
-            ((Err _) (continue)))
+          (let (:guess u32) (match (. guess (trim) (parse))
+                              ((Ok num) num)
+                              ((Err _) (continue))))
 
           (println! "You guessed: {}" guess)
 
@@ -52,20 +48,20 @@ test_transpile! {
         fn main() {
             println!("Guess the number!");
 
-            /* let secret_number = */ rand::thread_rng().gen_range(1 .. 101);
+            let secret_number = rand::thread_rng().gen_range(1 .. 101);
             // or
-            /* let secret_number = */ rand::thread_rng().gen_range(1 .. 101);
+            let secret_number = rand::thread_rng().gen_range(1 .. 101);
 
             loop {
                 println!("Please input your guess.");
 
-                /* let mut guess = */ String::new();
+                let mut guess = String::new();
 
                 io::stdin().read_line(&mut guess).expect("Failed to read line");
                 // or
                 io::stdin().read_line(&mut guess).expect("Failed to read line");
 
-                /* let guess: u32 = */ match guess.trim().parse() {
+                let guess: u32 = match guess.trim().parse() {
                     Ok(num) => num,
                     Err(_) => continue,
                 };
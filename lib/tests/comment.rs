@@ -0,0 +1,144 @@
+mod common;
+
+test_transpile! {
+    block_comments: r##"
+        #| A block comment
+           spanning multiple lines |#
+        (fn main () #| inline |# (f x)
+          #| nested #| block |# comment
+             (g) |#
+          (h "#| not a comment |#"))
+    "## => {
+        fn main() {
+            f(x);
+            h("#| not a comment |#");
+        }
+    }
+
+    datum_comments: r##"
+        #;(fn unused ()
+            (f))
+        (fn main ()
+          #; (f x)
+          (g #;x y)
+          #; #; (a) (b)
+          (h [1 #;2 3])
+          #;(fn nested ()
+              #;(i)
+              (j))
+          (k))
+    "## => {
+        fn main() {
+            g(y);
+            h([1, 3]);
+            k();
+        }
+    }
+
+    kept_comments (parse_with_comments): r##"
+        ;;;! Crate documentation
+        (use std::fmt)
+
+        ;;; A point.
+        ;;;
+        ;;; With two coordinates.
+        (struct Point
+          ;;; Abscissa
+          :x i32 ;; plain comments are dropped
+          ;;; Ordinate
+          :y i32)
+
+        (enum Shape
+          ;;;; Not a doc comment
+          ;;; A circle
+          (Circle :r f64)
+          Square)
+
+        (impl Point
+          ;;; Creates the origin.
+          (fn origin () -> Self
+            ;; comments in blocks and in arrays
+            (let a [1 ;; one
+                    2])
+            (Self :x 0 :y 0)
+            ;; trailing comment
+            ))
+    "## => {
+        //! Crate documentation
+        use std::fmt;
+
+        /// A point.
+        ///
+        /// With two coordinates.
+        struct Point {
+            /// Abscissa
+            x: i32,
+            /// Ordinate
+            y: i32
+        }
+
+        enum Shape {
+            /// A circle
+            Circle { r: f64 },
+            Square
+        }
+
+        impl Point {
+            /// Creates the origin.
+            fn origin() -> Self {
+                let a = [1, 2];
+                Self { x: 0, y: 0 }
+            }
+        }
+    }
+}
+
+#[test]
+fn top_level_comments() {
+    use pretty_assertions::assert_eq;
+    use srs::{parse_with_comments, transpile_parsed};
+
+    let srs = r##"
+        ;;;! Crate documentation
+        ;; A plain comment
+        (use std::fmt)
+
+        ;; Before the documentation
+        ;;; A point.
+        (struct Point :x i32 :y i32)
+        ;;;; Not a doc comment
+        (fn main ()
+          ;; dropped
+          (f))
+        ;; Trailing comment
+    "##;
+    assert_eq!(
+        transpile_parsed(parse_with_comments(srs)).unwrap(),
+        r##"//! Crate documentation
+// A plain comment
+use std::fmt;
+// Before the documentation
+/// A point.
+struct Point {
+    x: i32,
+    y: i32,
+}
+//// Not a doc comment
+fn main() {
+    f();
+}
+// Trailing comment
+"##
+    );
+}
+
+#[test]
+fn comment_separating_doc_comment() {
+    use srs::{parse_with_comments, transpile_parsed, ErrorKind};
+
+    let srs = ";;; A point.\n;; Between the documentation and the item\n(struct Point :x i32)";
+    let diagnostics = transpile_parsed(parse_with_comments(srs)).unwrap_err();
+    assert_eq!(diagnostics.len(), 1);
+    assert!(matches!(diagnostics[0].kind, ErrorKind::Syntax(_)));
+    assert_eq!(diagnostics[0].span.unwrap().start.line, 2);
+}
@@ -1,27 +1,25 @@
 #[macro_export]
 macro_rules! test_transpile {
-    ($($title: ident : $srs: expr => {$($rs: tt)*})*) => {
+    (@parse) => { parse };
+    (@parse $parse: ident) => { $parse };
+    ($($title: ident $(($parse: ident))? : $srs: expr => {$($rs: tt)*})*) => {
         $(
             #[test]
             fn $title() {
-                use srs::{parse, rustify};
+                #[allow(unused_imports)]
+                use srs::{parse, parse_with_comments, transpile_parsed};
 
                 use core::str::FromStr;
                 use proc_macro2::TokenStream;
                 use prettyplease::unparse;
                 use pretty_assertions::assert_eq;
 
-                let tok = parse($srs)
-                        .into_iter()
-                        .map(|e| rustify(&e.unwrap()).unwrap())
-                        .collect::<TokenStream>();
+                let a = transpile_parsed(test_transpile!(@parse $($parse)?)($srs))
+                        .unwrap_or_else(|e| panic!("Cannot transpile srs version: {:?}", e));
 
-                println!("{}", tok);
-
-                let [a, b] = [
-                    ("srs", tok),
-                    ("rs", TokenStream::from_str(stringify!{$($rs)*}).expect("Cannot tokenize rust version"))
-                ].map(|(i, x)| unparse(&syn::parse2(x).expect(&format!("syn cannot parse {}", i))));
+                let b = unparse(&syn::parse2(
+                    TokenStream::from_str(stringify!{$($rs)*}).expect("Cannot tokenize rust version")
+                ).expect("syn cannot parse rs"));
 
                 assert_eq!(a, b)
             }
@@ -0,0 +1,56 @@
+mod common;
+
+test_transpile! {
+    constants: r##"
+        (const (:MAX usize) 64)
+        (pub const (:NAMES &[&str]) (& ["a" "b"]))
+        (const (:ZERO Point) (Point :x 0 :y 0))
+
+        (fn main ()
+          (const (:LIMIT u8) (* 2 8))
+          (println! "{}" (+ MAX LIMIT)))
+    "## => {
+        const MAX: usize = 64;
+        pub const NAMES: &[&str] = &["a", "b"];
+        const ZERO: Point = Point { x: 0, y: 0 };
+
+        fn main() {
+            const LIMIT: u8 = 2 * 8;
+            println!("{}", MAX + LIMIT);
+        }
+    }
+
+    statics: r##"
+        (static (:GREETING &str) "hello")
+        (pub static (:TABLE &[u8]) (& [1 2 3]))
+        (static mut (:COUNTER u32) 0)
+
+        (fn bump () -> u32
+          (unsafe
+            (+= COUNTER 1)
+            COUNTER))
+    "## => {
+        static GREETING: &str = "hello";
+        pub static TABLE: &[u8] = &[1, 2, 3];
+        static mut COUNTER: u32 = 0;
+
+        fn bump() -> u32 {
+            unsafe {
+                COUNTER += 1;
+                COUNTER
+            }
+        }
+    }
+
+    type_aliases: r##"
+        (type Id u64)
+        (pub type Map<K V> (:: std collections HashMap <K V>))
+        (type Pair<T> (where (:T Clone)) (, T T))
+        (pub(crate) type Name Box<str>)
+    "## => {
+        type Id = u64;
+        pub type Map<K, V> = std::collections::HashMap::<K, V>;
+        type Pair<T> where T: Clone = (T, T);
+        pub(crate) type Name = Box<str>;
+    }
+}
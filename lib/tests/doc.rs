@@ -0,0 +1,196 @@
+mod common;
+
+test_transpile! {
+    items: r##"
+        ;;;! A geometry crate.
+        ;;;!
+        ;;;! Written in srs.
+
+        ;;; Computes the area of a circle.
+        ;;;
+        ;;; # Example
+        ;;; ```
+        ;;; assert_eq!(area(1.0), PI);
+        ;;; ```
+        (pub fn area ((:r f64)) -> f64
+          ;; Plain comments are not documentation
+          (* PI r r))
+
+        ;;; A 2D point.
+        (pub struct Point
+          ;;; Abscissa.
+          (pub :x f64)
+          ;;; Ordinate.
+          (pub :y f64))
+
+        ;;; A wrapper.
+        (struct Meters
+          ;;; The wrapped value.
+          f64)
+
+        ;;; Available shapes.
+        (enum Shape
+          ;;; A circle with its radius.
+          (Circle f64)
+          ;;; A square.
+          #[allow dead_code]
+          Square)
+    "## => {
+        //! A geometry crate.
+        //!
+        //! Written in srs.
+
+        /// Computes the area of a circle.
+        ///
+        /// # Example
+        /// ```
+        /// assert_eq!(area(1.0), PI);
+        /// ```
+        pub fn area(r: f64) -> f64 {
+            PI * r * r
+        }
+
+        /// A 2D point.
+        pub struct Point {
+            /// Abscissa.
+            pub x: f64,
+            /// Ordinate.
+            pub y: f64
+        }
+
+        /// A wrapper.
+        struct Meters(
+            /// The wrapped value.
+            f64
+        );
+
+        /// Available shapes.
+        enum Shape {
+            /// A circle with its radius.
+            Circle(f64),
+            /// A square.
+            #[allow(dead_code)]
+            Square
+        }
+    }
+
+    modules_and_traits: r##"
+        ;;; Shapes.
+        (pub mod shapes
+          ;;;! Inner documentation of the module.
+
+          ;;; Something with an area.
+          (pub trait Area
+            ;;; Returns the area.
+            (fn area (&self) -> f64)
+            ;;; The unit of measure.
+            (type Unit))
+
+          (impl Area for Square
+            ;;; The area of a square.
+            (fn area (&self) -> f64
+              (* (. self side) (. self side)))
+            (type Unit Meters)))
+
+        (fn main ()
+          (match shape
+            ;;; Squares only.
+            ((Square :side) side)
+            (_ 0)))
+    "## => {
+        /// Shapes.
+        pub mod shapes {
+            //! Inner documentation of the module.
+
+            /// Something with an area.
+            pub trait Area {
+                /// Returns the area.
+                fn area(&self) -> f64;
+                /// The unit of measure.
+                type Unit;
+            }
+
+            impl Area for Square {
+                /// The area of a square.
+                fn area(&self) -> f64 {
+                    self.side * self.side
+                }
+                type Unit = Meters;
+            }
+        }
+
+        fn main() {
+            match shape {
+                /// Squares only.
+                Square { side } => side,
+                _ => 0,
+            };
+        }
+    }
+
+    dropped_in_expressions: r##"
+        (use (:: std
+               ;;; Not documentation.
+               io))
+
+        (fn main ()
+          (f ;;; Not documentation either.
+             x
+             [1 ;;; Nor here.
+              2]))
+
+        (fn g<T ;;; Nor in generics.
+              U> ((:a T) ;;; Nor in parameters.
+                  (:b U)))
+    "## => {
+        use std::io;
+
+        fn main() {
+            f(x, [1, 2]);
+        }
+
+        fn g<T, U>(a: T, b: U) {}
+    }
+
+    trailing: r##"
+        (struct Named :a u8 ;;; Documents nothing
+          )
+        (struct Tuple u8 ;;; Documents nothing
+          )
+        (enum E A ;;; Documents nothing
+          )
+        (trait T
+          (fn f (&self) ;;; Documents nothing
+            ))
+        (impl T for Named
+          (fn f (&self)) ;;; Documents nothing
+          )
+        (fn main ()
+          (f)
+          (match x
+            (1 2) ;;; Documents nothing
+            )
+          ;;; Documents nothing
+          )
+    "## => {
+        struct Named {
+            a: u8
+        }
+        struct Tuple(u8);
+        enum E {
+            A
+        }
+        trait T {
+            fn f(&self);
+        }
+        impl T for Named {
+            fn f(&self) {}
+        }
+        fn main() {
+            f();
+            match x {
+                1 => 2,
+            };
+        }
+    }
+}
@@ -0,0 +1,179 @@
+mod common;
+
+test_transpile! {
+    simple_if: r##"
+        (fn main ()
+          (if (< x 0)
+            (println! "negative"))
+          (if (>= x 10)
+            (println! "big")
+            (println! "small")))
+    "## => {
+        fn main() {
+            if x < 0 {
+                println!("negative");
+            };
+            if x >= 10 {
+                println!("big");
+            } else {
+                println!("small");
+            };
+        }
+    }
+
+    else_if: r##"
+        (fn main ()
+          (if (< x 0)
+            (println! "negative")
+            (if (== x 0)
+              (println! "zero")
+              (println! "positive"))))
+    "## => {
+        fn main() {
+            if x < 0 {
+                println!("negative");
+            } else if x == 0 {
+                println!("zero");
+            } else {
+                println!("positive");
+            };
+        }
+    }
+
+    if_expression: r##"
+        (fn main ()
+          (f (if (> a b) a b))
+          (+ 1 (if c
+                 (do (g) 2)
+                 3)))
+    "## => {
+        fn main() {
+            f(if a > b { a } else { b });
+            1 + if c {
+                g();
+                2
+            } else {
+                3
+            };
+        }
+    }
+
+    multi_expression_branches: r##"
+        (fn main ()
+          (if (! done)
+            (do
+              (step)
+              (= done (check)))
+            (do
+              (println! "done")
+              (return))))
+    "## => {
+        fn main() {
+            if !done {
+                step();
+                done = check();
+            } else {
+                println!("done");
+                return;
+            };
+        }
+    }
+
+    do_block: r##"
+        (fn main ()
+          (do
+            (f)
+            (g)))
+    "## => {
+        fn main() {
+            {
+                f();
+                g();
+            }
+        }
+    }
+
+    for_loop: r##"
+        (fn main ()
+          (for x (.. 0 10)
+            (println! "{}" x))
+          (for (Point :x :y) (. points (iter))
+            (println! "{}" x)
+            (println! "{}" y)))
+    "## => {
+        fn main() {
+            for x in 0 .. 10 {
+                println!("{}", x);
+            }
+            for Point {x, y} in points.iter() {
+                println!("{}", x);
+                println!("{}", y);
+            }
+        }
+    }
+
+    while_loop: r##"
+        (fn main ()
+          (while (< i 10)
+            (+= i 1))
+          (while (let (Some x) (. stack (pop)))
+            (println! "{}" x)))
+    "## => {
+        fn main() {
+            while i < 10 {
+                i += 1;
+            }
+            while let Some(x) = stack.pop() {
+                println!("{}", x);
+            }
+        }
+    }
+
+    if_let: r##"
+        (fn main ()
+          (if (let (Some x) y)
+            (f x)
+            (g)))
+    "## => {
+        fn main() {
+            if let Some(x) = y {
+                f(x);
+            } else {
+                g();
+            };
+        }
+    }
+
+    loop_labels: r##"
+        (fn main ()
+          (for 'outer x xs
+            (while 'inner (< i x)
+              (if (== i 3)
+                (continue 'outer))
+              (if (== i 5)
+                (break 'inner)))
+            (loop 'retry
+              (break 'retry)))
+          (loop 'value
+            (break 'value 42)))
+    "## => {
+        fn main() {
+            'outer: for x in xs {
+                'inner: while i < x {
+                    if i == 3 {
+                        continue 'outer;
+                    };
+                    if i == 5 {
+                        break 'inner;
+                    };
+                }
+                'retry: loop {
+                    break 'retry;
+                }
+            }
+            'value: loop {
+                break 'value 42;
+            }
+        }
+    }
+}
@@ -0,0 +1,99 @@
+mod common;
+
+test_transpile! {
+    parameters: r##"
+        (fn add ((:a i32) (:b i32)) -> i32
+          (+ a b))
+
+        (fn print ((mut :x u8) (: (Point :x :y) Point) (mut :z bool))
+          (+= x 1)
+          (println! "{} {} {} {}" x y z))
+    "## => {
+        fn add(a: i32, b: i32) -> i32 {
+            a + b
+        }
+
+        fn print(mut x: u8, Point {x, y}: Point, mut z: bool) {
+            x += 1;
+            println!("{} {} {} {}", x, y, z);
+        }
+    }
+
+    generic_types: r##"
+        (fn first ((:v &Vec<Option<u8>>)) -> Option<u8>
+          (. v (get 0) (copied) (flatten)))
+    "## => {
+        fn first(v: &Vec<Option<u8>>) -> Option<u8> {
+            v.get(0).copied().flatten()
+        }
+    }
+
+    generics: r##"
+        (fn largest<(:T PartialOrd Copy) U> ((:list &[T]) (:_other U)) -> T
+          (largest_of list))
+
+        (fn convert<T> ((:x T)) -> String (where (:T Into<String> Clone))
+          (. x (into)))
+    "## => {
+        fn largest<T: PartialOrd + Copy, U>(list: &[T], _other: U) -> T {
+            largest_of(list)
+        }
+
+        fn convert<T>(x: T) -> String where T: Into<String> + Clone {
+            x.into()
+        }
+    }
+
+    qualifiers: r##"
+        (pub const fn zero () -> u8 0)
+        (async fn fetch () (. (get) await))
+        (pub unsafe fn danger ((:p *const u8)) -> u8 (* p))
+        (extern "C" fn callback ((:n i32)) (f n))
+        (pub unsafe extern "C" fn exported ())
+    "## => {
+        pub const fn zero() -> u8 {
+            0
+        }
+        async fn fetch() {
+            get().await;
+        }
+        pub unsafe fn danger(p: *const u8) -> u8 {
+            *p
+        }
+        extern "C" fn callback(n: i32) {
+            f(n);
+        }
+        pub unsafe extern "C" fn exported() {}
+    }
+
+    closures: r##"
+        (fn main ()
+          (. v (iter) (map (fn (x) (* x 2))))
+          (f (fn () (g)) (fn (a b) (+ a b)))
+          (spawn (fn move () (println! "{}" x) (h x)))
+          (. xs (sort_by_key (fn ((:k &u8)) -> u8 (* k 2)))))
+    "## => {
+        fn main() {
+            v.iter().map(|x| x * 2);
+            f(|| g(), |a, b| a + b);
+            spawn(move || {
+                println!("{}", x);
+                h(x)
+            });
+            xs.sort_by_key(|k: &u8| -> u8 { k * 2 });
+        }
+    }
+
+    closures_precedence: r##"
+        (fn main ()
+          ((fn (x) (+ x 1)) 2)
+          (. (fn () 1) clone)
+          (= f (fn (x) x)))
+    "## => {
+        fn main() {
+            (|x| x + 1)(2);
+            (|| 1).clone;
+            f = |x| x;
+        }
+    }
+}
@@ -0,0 +1,116 @@
+mod common;
+
+test_transpile! {
+    inherent_impl: r##"
+        (struct Counter :count u32)
+
+        (impl Counter
+          (pub fn new () -> Self
+            (Self :count 0))
+
+          (fn get (&self) -> u32
+            (. self count))
+
+          (fn add (&mut self (:n u32))
+            (+= (. self count) n))
+
+          (fn into_inner (self) -> u32
+            (. self count))
+
+          (fn reset (mut self) -> Self
+            (= (. self count) 0)
+            self)
+
+          (fn boxed ((:self Box<Self>)) -> u32
+            (. self count)))
+    "## => {
+        struct Counter {
+            count: u32
+        }
+
+        impl Counter {
+            pub fn new() -> Self {
+                Self { count: 0 }
+            }
+
+            fn get(&self) -> u32 {
+                self.count
+            }
+
+            fn add(&mut self, n: u32) {
+                self.count += n;
+            }
+
+            fn into_inner(self) -> u32 {
+                self.count
+            }
+
+            fn reset(mut self) -> Self {
+                self.count = 0;
+                self
+            }
+
+            fn boxed(self: Box<Self>) -> u32 {
+                self.count
+            }
+        }
+    }
+
+    trait_impl: r##"
+        (impl Default for Counter
+          (fn default () -> Self
+            ((:: Self new))))
+
+        (impl (:: fmt Display) for Counter
+          (fn fmt (&self (:f &mut (:: fmt Formatter))) -> (:: fmt Result)
+            (write! f "{}" (. self count))))
+    "## => {
+        impl Default for Counter {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl fmt::Display for Counter {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}", self.count)
+            }
+        }
+    }
+
+    generic_impl: r##"
+        (impl<T> Wrapper<T>
+          (fn inner (&self) -> &T
+            (& (. self inner))))
+
+        (impl<(:T Clone)> From<Vec<T>> for Wrapper<T>
+          (fn from ((:v Vec<T>)) -> Self
+            (Self :inner v)))
+
+        (impl<T> Clone for Wrapper<T> (where (:T Clone))
+          (fn clone (&self) -> Self
+            (Self :inner (. self inner (clone)))))
+
+        (unsafe impl<T> Send for Wrapper<T>)
+    "## => {
+        impl<T> Wrapper<T> {
+            fn inner(&self) -> &T {
+                &self.inner
+            }
+        }
+
+        impl<T: Clone> From<Vec<T>> for Wrapper<T> {
+            fn from(v: Vec<T>) -> Self {
+                Self { inner: v }
+            }
+        }
+
+        impl<T> Clone for Wrapper<T> where T: Clone {
+            fn clone(&self) -> Self {
+                Self { inner: self.inner.clone() }
+            }
+        }
+
+        unsafe impl<T> Send for Wrapper<T> {}
+    }
+}
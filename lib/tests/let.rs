@@ -0,0 +1,66 @@
+mod common;
+
+test_transpile! {
+    simple_let: r##"
+        (fn main ()
+          (let x 5)
+          (let mut y (+ x 1))
+          (let (:z u32) 7)
+          (let mut (:v Vec<u8>) ((:: Vec new))))
+    "## => {
+        fn main() {
+            let x = 5;
+            let mut y = x + 1;
+            let z: u32 = 7;
+            let mut v: Vec<u8> = Vec::new();
+        }
+    }
+
+    uninitialised: r##"
+        (fn main ()
+          (let x)
+          (let (:y &str))
+          (= x 1))
+    "## => {
+        fn main() {
+            let x;
+            let y: &str;
+            x = 1;
+        }
+    }
+
+    destructuring: r##"
+        (fn main ()
+          (let (, a b) (, 1 2))
+          (let (, (mut c) (ref d) (ref mut e) _) t)
+          (let (: (, d e) (, u8 bool)) (f))
+          (let (Point :x :y) p)
+          (let [first second ..] arr)
+          (let (, single) (, 1)))
+    "## => {
+        fn main() {
+            let (a, b) = (1, 2);
+            let (mut c, ref d, ref mut e, _) = t;
+            let (d, e): (u8, bool) = f();
+            let Point { x, y } = p;
+            let [first, second, ..] = arr;
+            let (single,) = (1,);
+        }
+    }
+
+    let_else: r##"
+        (fn main ()
+          (let (Some x) opt else (return))
+          (let (Ok (:: Kind A)) res else
+            (println! "not A")
+            (return)))
+    "## => {
+        fn main() {
+            let Some(x) = opt else { return; };
+            let Ok(Kind::A) = res else {
+                println!("not A");
+                return;
+            };
+        }
+    }
+}
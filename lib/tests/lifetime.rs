@@ -0,0 +1,76 @@
+mod common;
+
+test_transpile! {
+    references: r##"
+        (fn longest<'a> ((:x &'a str) (:y &'a str)) -> &'a str
+          (if (> (. x (len)) (. y (len))) x y))
+
+        (fn push<'a T> ((:v &'a mut Vec<T>) (:x T)) -> &'a mut T
+          (. v (push x))
+          (. v (last_mut) (unwrap)))
+
+        (fn swap ((:a (&mut 'static u8)) (:b (& 'static mut u8))))
+    "## => {
+        fn longest<'a>(x: &'a str, y: &'a str) -> &'a str {
+            if x.len() > y.len() { x } else { y }
+        }
+
+        fn push<'a, T>(v: &'a mut Vec<T>, x: T) -> &'a mut T {
+            v.push(x);
+            v.last_mut().unwrap()
+        }
+
+        fn swap(a: &'static mut u8, b: &'static mut u8) {}
+    }
+
+    receivers: r##"
+        (impl<'a> Parser<'a>
+          (fn peek (&'a self) -> &'a str
+            (. self input))
+          (fn advance (&'a mut self (:n usize))
+            (+= (. self pos) n)))
+    "## => {
+        impl<'a> Parser<'a> {
+            fn peek(&'a self) -> &'a str {
+                self.input
+            }
+            fn advance(&'a mut self, n: usize) {
+                self.pos += n;
+            }
+        }
+    }
+
+    lifetime_bounds: r##"
+        (struct Parser<'a (:'b 'a) (:T 'a Clone)>
+          :input &'a str
+          :items Vec<&'b T>)
+
+        (impl<'a T> Iterator for Iter<'a T> (where (:T 'a Debug))
+          (type Item &'a T))
+    "## => {
+        struct Parser<'a, 'b: 'a, T: 'a + Clone> {
+            input: &'a str,
+            items: Vec<&'b T>
+        }
+
+        impl<'a, T> Iterator for Iter<'a, T> where T: 'a + Debug {
+            type Item = &'a T;
+        }
+    }
+
+    higher_ranked_bounds: r##"
+        (fn apply<F> ((:f F)) (where (:F for<'a> Visitor<'a>))
+          (. f (visit)))
+
+        (fn call<T> ((:x T)) (where (: for<'a> &'a T Display))
+          (println! "{}" (& x)))
+    "## => {
+        fn apply<F>(f: F) where F: for<'a> Visitor<'a> {
+            f.visit();
+        }
+
+        fn call<T>(x: T) where for<'a> &'a T: Display {
+            println!("{}", &x);
+        }
+    }
+}
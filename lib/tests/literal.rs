@@ -0,0 +1,104 @@
+mod common;
+
+test_transpile! {
+    chars: r##"
+        (fn main ()
+          (let c 'a')
+          (match c
+            ('(' (open))
+            (')' (close))
+            (' ' (space))
+            ('\'' (quote))
+            ('\n' (newline))
+            ('\u{1F600}' (smile))
+            ('é' (accent))
+            (_ (other)))
+          (println! "{}" (. "a;b" (split ';') (count))))
+    "## => {
+        fn main() {
+            let c = 'a';
+            match c {
+                '(' => open(),
+                ')' => close(),
+                ' ' => space(),
+                '\'' => quote(),
+                '\n' => newline(),
+                '\u{1F600}' => smile(),
+                'é' => accent(),
+                _ => other(),
+            };
+            println!("{}", "a;b".split(';').count());
+        }
+    }
+
+    byte_chars: r##"
+        (fn is_digit ((:b u8)) -> bool
+          (&& (>= b b'0') (<= b b'9') (!= b b'\xFF')))
+    "## => {
+        fn is_digit(b: u8) -> bool {
+            b >= b'0' && b <= b'9' && b != b'\xFF'
+        }
+    }
+
+    chars_and_lifetimes: r##"
+        (fn first<'a> ((:s &'a str)) -> &'a str
+          (. s (split 'x') (next) (unwrap_or (& 'b'))))
+
+        (fn main ()
+          (f &'a' &'\n'))
+    "## => {
+        fn first<'a>(s: &'a str) -> &'a str {
+            s.split('x').next().unwrap_or(&'b')
+        }
+
+        fn main() {
+            f(&'a', &'\n');
+        }
+    }
+
+    strings: r###"
+        (fn main ()
+          (let re (Regex::new r"^\d+;(\w+)$"))
+          (let json r#"{"name": "srs", "tags": ["a" "b"]}"#)
+          (let nested r##"a "# b"##)
+          (let bytes b"a\x00;b")
+          (let raw_bytes br#"\"quoted\""#)
+          (let c_str c"hello")
+          (let raw_c_str cr"\0")
+          (let r#type "escaped \" quote")
+          (f r#type))
+    "### => {
+        fn main() {
+            let re = Regex::new(r"^\d+;(\w+)$");
+            let json = r#"{"name": "srs", "tags": ["a" "b"]}"#;
+            let nested = r##"a "# b"##;
+            let bytes = b"a\x00;b";
+            let raw_bytes = br#"\"quoted\""#;
+            let c_str = c"hello";
+            let raw_c_str = cr"\0";
+            let r#type = "escaped \" quote";
+            f(r#type);
+        }
+    }
+
+    multi_line_strings: r###"
+        (fn main ()
+          (println! "first line
+second; line")
+          (let query r#"
+SELECT "name"
+FROM t"#)
+          (f "a\
+b"))
+    "### => {
+        fn main() {
+            println!("first line
+second; line");
+            let query = r#"
+SELECT "name"
+FROM t"#;
+            f("a\
+b");
+        }
+    }
+}
@@ -0,0 +1,56 @@
+mod common;
+
+test_transpile! {
+    inline_module: r##"
+        (mod geometry
+          (use std::f64::consts::PI)
+
+          (pub fn area ((:r f64)) -> f64
+            (* PI r r))
+
+          (mod tests))
+
+        (fn main ()
+          (println! "{}" (geometry::area 1.0)))
+    "## => {
+        mod geometry {
+            use std::f64::consts::PI;
+
+            pub fn area(r: f64) -> f64 {
+                PI * r * r
+            }
+
+            mod tests;
+        }
+
+        fn main() {
+            println!("{}", geometry::area(1.0));
+        }
+    }
+
+    module_declarations: r##"
+        (mod parser)
+        (pub mod rustify)
+    "## => {
+        mod parser;
+        pub mod rustify;
+    }
+
+    restricted_visibility: r##"
+        (pub(crate) mod a
+          (pub(super) fn f ())
+          (pub(self) struct S
+            (pub(crate) :x u8)
+            (pub(in crate::a) :y u8))
+          (pub(in (:: crate a)) struct T (pub(super) u8)))
+    "## => {
+        pub(crate) mod a {
+            pub(super) fn f() {}
+            pub(self) struct S {
+                pub(crate) x: u8,
+                pub(in crate::a) y: u8
+            }
+            pub(in crate::a) struct T(pub(super) u8);
+        }
+    }
+}
@@ -2,10 +2,10 @@ mod common;
 
 test_transpile! {
     simple_struct: r##"
-        ;; (struct Test
-        ;;   :a u8
-        ;;   :b String
-        ;;   :c bool)
+        (struct Test
+          :a u8
+          :b String
+          :c bool)
 
         (fn main ()
           (match x
@@ -14,11 +14,11 @@ test_transpile! {
             ((Test ..)))
           ((:: Extern Test) :c true (.. f)))
     "## => {
-        /* struct Test {
+        struct Test {
             a: u8,
             b: String,
             c: bool
-        } */
+        }
 
         fn main() {
             match x {
@@ -44,26 +44,22 @@ test_transpile! {
     }
 
     tuple_struct: r##"
-        ;; (struct Test u32 bool)
+        (struct Test u32 bool)
 
         (fn main ()
           (Test 1 false))
     "## => {
-        // struct Test(u32, bool);
+        struct Test(u32, bool);
 
         fn main() {
             Test(1, false);
         }
     }
 
-    /* pub_struct: r##"
+    pub_struct: r##"
         (pub struct Test
           :a u8
           (pub :b String)
-          ;; or (pub :b) String
-          ;; or :b pub String
-          ;; or :b (pub String)
-          ;; or other ?
           :c bool)
 
         (pub struct Tuple u32 (pub bool))
@@ -75,7 +71,7 @@ test_transpile! {
         }
 
         pub struct Tuple(u32, pub bool);
-    } */
+    }
 
     struct_update: r##"
         (fn main ()
@@ -85,4 +81,30 @@ test_transpile! {
             Test {a: 1, b, ..other};
         }
     }
+
+    generic_struct: r##"
+        (struct Wrapper<T> :inner Vec<T> :len usize)
+
+        (struct Pair<(:T Clone)> T T)
+
+        (struct Bounded<T U> (where (:T Clone) (:U Default))
+          :a T
+          :b Option<U>)
+
+        (struct Tagged<T> (where (:T Copy)) (pub T) Box<[u8]>)
+    "## => {
+        struct Wrapper<T> {
+            inner: Vec<T>,
+            len: usize
+        }
+
+        struct Pair<T: Clone>(T, T);
+
+        struct Bounded<T, U> where T: Clone, U: Default {
+            a: T,
+            b: Option<U>
+        }
+
+        struct Tagged<T>(pub T, Box<[u8]>) where T: Copy;
+    }
 }
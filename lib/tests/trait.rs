@@ -0,0 +1,97 @@
+mod common;
+
+test_transpile! {
+    simple_trait: r##"
+        (pub trait Shape
+          (fn area (&self) -> f64)
+          (fn name (&self) -> String
+            ((:: String from) "shape"))
+          (fn scale (&mut self (:k f64))))
+    "## => {
+        pub trait Shape {
+            fn area(&self) -> f64;
+            fn name(&self) -> String {
+                String::from("shape")
+            }
+            fn scale(&mut self, k: f64);
+        }
+    }
+
+    associated_items: r##"
+        (trait Container
+          (type Item)
+          (type (:Iter Iterator Clone))
+          (const (:CAPACITY usize))
+          (const (:NAME &str) "container")
+          (fn get (&self (:i usize)) -> Option<Self::Item>)
+          (fn first (&self) -> Option<Self::Item>
+            (. self (get 0))))
+
+        (impl Container for Stack
+          (type Item u32)
+          (type Iter (:: std vec IntoIter <u32>))
+          (const (:CAPACITY usize) 16)
+          (fn get (&self (:i usize)) -> Option<u32>
+            (. self items (get i) (copied))))
+    "## => {
+        trait Container {
+            type Item;
+            type Iter: Iterator + Clone;
+            const CAPACITY: usize;
+            const NAME: &str = "container";
+            fn get(&self, i: usize) -> Option<Self::Item>;
+            fn first(&self) -> Option<Self::Item> {
+                self.get(0)
+            }
+        }
+
+        impl Container for Stack {
+            type Item = u32;
+            type Iter = std::vec::IntoIter::<u32>;
+            const CAPACITY: usize = 16;
+            fn get(&self, i: usize) -> Option<u32> {
+                self.items.get(i).copied()
+            }
+        }
+    }
+
+    generic_associated_types: r##"
+        (trait Family
+          (type Member<T> (where (:T Clone))))
+
+        (impl Family for VecFamily
+          (type Member<T> (where (:T Clone)) Vec<T>))
+    "## => {
+        trait Family {
+            type Member<T> where T: Clone;
+        }
+
+        impl Family for VecFamily {
+            type Member<T> = Vec<T> where T: Clone;
+        }
+    }
+
+    supertraits_and_generics: r##"
+        (trait Named (: Debug Clone)
+          (fn name (&self) -> String))
+
+        (pub trait Convert<T> (: Sized) (where (:T Default))
+          (fn convert (self) -> T)
+          (unsafe fn convert_unchecked (self) -> T ((:: T default))))
+
+        (trait Empty)
+    "## => {
+        trait Named: Debug + Clone {
+            fn name(&self) -> String;
+        }
+
+        pub trait Convert<T>: Sized where T: Default {
+            fn convert(self) -> T;
+            unsafe fn convert_unchecked(self) -> T {
+                T::default()
+            }
+        }
+
+        trait Empty {}
+    }
+}